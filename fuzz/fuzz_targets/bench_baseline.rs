@@ -0,0 +1,6 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = adventofcode2020::fuzz::target("bench_baseline").unwrap().run(data);
+});
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::diagnostic::Diagnostic;
+use super::error::{Error, Result};
+use super::solution::{Answer, Answers};
+
+/// Default location of the expected answers, relative to the working directory.
+pub const DEFAULT_PATH: &str = "answers.toml";
+
+/// The outcome of checking one computed answer against the expected answers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: Answer },
+    Unknown,
+}
+
+impl Verdict {
+    pub fn is_fail(&self) -> bool {
+        matches!(self, Verdict::Fail { .. })
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected } => write!(f, "FAIL (expected {})", expected),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Expected answers keyed by day and part.
+///
+/// Stored as a small subset of TOML, one table per day:
+///
+/// ```toml
+/// [day01]
+/// part1 = 870331
+/// part2 = "text answers are quoted"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExpectedAnswers {
+    answers: BTreeMap<(u32, u8), Answer>,
+}
+
+impl ExpectedAnswers {
+    /// Load the expected answers from `path`. A missing file has no answers.
+    pub fn load(path: &Path) -> Result<ExpectedAnswers> {
+        match fs::read_to_string(path) {
+            Ok(s) => ExpectedAnswers::parse(&s),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(ExpectedAnswers::default()),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, self.to_toml())?)
+    }
+
+    pub fn parse(input: &str) -> Result<ExpectedAnswers> {
+        let mut expected = ExpectedAnswers::default();
+        let mut day = None;
+        for (i_line, source_line) in input.lines().enumerate() {
+            let line = source_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let err = |msg: &str| Error::from(Diagnostic::spanning(i_line + 1, source_line, line, msg));
+            if let Some(table) = line.strip_prefix('[') {
+                let number = table.strip_suffix(']')
+                    .and_then(|t| t.strip_prefix("day"))
+                    .and_then(|n| n.parse::<u32>().ok())
+                    .ok_or_else(|| err("expected a table header like [day01]"))?;
+                day = Some(number);
+                continue;
+            }
+
+            let day = day.ok_or_else(|| err("answer outside of a [dayNN] table"))?;
+            let (part, answer) = parse_answer_line(i_line + 1, source_line)?;
+            expected.answers.insert((day, part), answer);
+        }
+        Ok(expected)
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        let mut current_day = None;
+        for (&(day, part), answer) in &self.answers {
+            if current_day != Some(day) {
+                if current_day.is_some() { out.push('\n'); }
+                out.push_str(&format!("[day{:02}]\n", day));
+                current_day = Some(day);
+            }
+            let value = match answer {
+                Answer::Number(n) => n.to_string(),
+                Answer::Text(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            };
+            out.push_str(&format!("part{} = {}\n", part, value));
+        }
+        out
+    }
+
+    pub fn get(&self, day: u32, part: u8) -> Option<&Answer> {
+        self.answers.get(&(day, part))
+    }
+
+    pub fn insert(&mut self, day: u32, part: u8, answer: Answer) {
+        self.answers.insert((day, part), answer);
+    }
+
+    /// Record both answers of `day`, replacing any previous ones.
+    pub fn record(&mut self, day: u32, answers: &Answers) {
+        self.insert(day, 1, answers.part1.clone());
+        self.insert(day, 2, answers.part2.clone());
+    }
+
+    pub fn check(&self, day: u32, part: u8, answer: &Answer) -> Verdict {
+        match self.get(day, part) {
+            Some(expected) if expected == answer => Verdict::Pass,
+            Some(expected) => Verdict::Fail { expected: expected.clone() },
+            None => Verdict::Unknown,
+        }
+    }
+
+    /// Check both answers of `day`.
+    pub fn check_all(&self, day: u32, answers: &Answers) -> [Verdict; 2] {
+        [self.check(day, 1, &answers.part1), self.check(day, 2, &answers.part2)]
+    }
+}
+
+/// Parse a `partN = answer` line, where the answer is a number or a quoted
+/// string. `line` is the 1-based line number used in diagnostics.
+pub fn parse_answer_line(line: usize, source_line: &str) -> Result<(u8, Answer)> {
+    let trimmed = source_line.trim();
+    let err = |msg: &str| Error::from(Diagnostic::spanning(line, source_line, trimmed, msg));
+    let (key, value) = trimmed.split_once('=')
+        .ok_or_else(|| err("expected 'partN = answer'"))?;
+    let part = match key.trim() {
+        "part1" => 1,
+        "part2" => 2,
+        k => return Err(err(&format!("unknown key '{}'", k))),
+    };
+    let value = value.trim();
+    let answer = if let Some(s) = value.strip_prefix('"') {
+        let s = s.strip_suffix('"').ok_or_else(|| err("unterminated string"))?;
+        Answer::Text(s.replace("\\\"", "\"").replace("\\\\", "\\"))
+    } else {
+        Answer::Number(value.parse().map_err(|e| err(&format!("{}", e)))?)
+    };
+    Ok((part, answer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+# Expected answers
+[day01]
+part1 = 514579
+part2 = \"abc\"
+
+[day02]
+part1 = 2
+";
+
+    #[test]
+    fn parse_and_check() {
+        let expected = ExpectedAnswers::parse(EXAMPLE).unwrap();
+        assert_eq!(expected.check(1, 1, &Answer::Number(514579)), Verdict::Pass);
+        assert_eq!(expected.check(1, 2, &Answer::Text(String::from("abc"))), Verdict::Pass);
+        assert_eq!(expected.check(2, 1, &Answer::Number(3)),
+                   Verdict::Fail { expected: Answer::Number(2) });
+        assert_eq!(expected.check(2, 2, &Answer::Number(3)), Verdict::Unknown);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut expected = ExpectedAnswers::default();
+        expected.insert(7, 2, Answer::Text(String::from("quote \" and \\")));
+        expected.insert(1, 1, Answer::Number(1));
+        let toml = expected.to_toml();
+        assert!(toml.starts_with("[day01]\npart1 = 1\n\n[day07]"));
+        assert_eq!(ExpectedAnswers::parse(&toml).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let line_of = |input| match ExpectedAnswers::parse(input) {
+            Err(Error::Parse(d)) => d.line,
+            r => panic!("expected a parse error, got {:?}", r),
+        };
+        assert_eq!(line_of("part1 = 1"), 1);
+        assert_eq!(line_of("[day01]\npart3 = 1"), 2);
+        assert!(ExpectedAnswers::parse("[dayX]").is_err());
+        assert!(ExpectedAnswers::parse("[day01]\npart1 = \"x").is_err());
+    }
+}
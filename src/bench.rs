@@ -0,0 +1,329 @@
+//! Repeated timing of the registered days, with baselines to compare against.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use super::error::{Error, Result};
+use super::runner::format_duration;
+use super::solution::{Day, Timings};
+
+/// Default location of the saved baseline, relative to the working directory.
+pub const DEFAULT_BASELINE_PATH: &str = "bench-baseline.txt";
+/// Iterations per day unless told otherwise.
+pub const DEFAULT_ITERATIONS: usize = 50;
+/// Slowdown of the mean, in percent, flagged as a regression by default.
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+/// Standard deviations the mean must also move by to count as a regression,
+/// so that a noisy stage is not flagged on the percentage alone.
+pub const NOISE_SIGMAS: f64 = 2.0;
+
+/// The stages timed for every day.
+pub const STAGES: [&str; 4] = ["parse", "part1", "part2", "total"];
+
+/// Summary statistics of a set of samples.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub mean: Duration,
+    pub median: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn of(samples: &[Duration]) -> Stats {
+        if samples.is_empty() {
+            return Stats::default();
+        }
+        let secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / secs.len() as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / secs.len() as f64;
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) { (sorted[mid - 1] + sorted[mid]) / 2 } else { sorted[mid] };
+
+        Stats {
+            mean: Duration::from_secs_f64(mean),
+            median,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// The benchmark results of one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bench {
+    pub day: u32,
+    pub input_bytes: usize,
+    pub iterations: usize,
+    /// Statistics per stage, in the order of [`STAGES`].
+    pub stages: [Stats; 4],
+}
+
+impl Bench {
+    /// Input bytes processed per second, from the mean total time.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.stages[3].mean.as_secs_f64();
+        if secs > 0.0 { self.input_bytes as f64 / secs } else { 0.0 }
+    }
+}
+
+/// Solve `day` on `input` `iterations` times, after one untimed warm-up run.
+pub fn bench_day(day: &Day, input: &str, iterations: usize) -> Result<Bench> {
+    (day.solve)(input)?;
+    let mut samples: [Vec<Duration>; 4] = Default::default();
+    for _ in 0..iterations.max(1) {
+        let t: Timings = (day.solve)(input)?.timings;
+        for (stage, d) in samples.iter_mut().zip([t.parse, t.part1, t.part2, t.total()]) {
+            stage.push(d);
+        }
+    }
+    Ok(Bench {
+        day: day.number,
+        input_bytes: input.len(),
+        iterations: iterations.max(1),
+        stages: [Stats::of(&samples[0]), Stats::of(&samples[1]), Stats::of(&samples[2]), Stats::of(&samples[3])],
+    })
+}
+
+/// Format a throughput with a binary unit prefix.
+pub fn format_throughput(bytes_per_sec: f64) -> String {
+    let units = ["B/s", "KiB/s", "MiB/s", "GiB/s"];
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", value, units[unit])
+}
+
+/// Render one block per day with the statistics of every stage.
+pub fn table(benches: &[Bench]) -> String {
+    let mut out = String::new();
+    for b in benches {
+        writeln!(out, "Day {:02}: {} iterations, {} bytes, {}",
+                 b.day, b.iterations, b.input_bytes, format_throughput(b.throughput())).unwrap();
+        writeln!(out, "  {:<6} {:>12} {:>12} {:>12}", "", "Mean", "Median", "Std dev").unwrap();
+        for (name, s) in STAGES.iter().zip(&b.stages) {
+            writeln!(out, "  {:<6} {:>12} {:>12} {:>12}",
+                     name, format_duration(s.mean), format_duration(s.median), format_duration(s.stddev)).unwrap();
+        }
+    }
+    out
+}
+
+/// The mean and spread of one stage in a baseline.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StageBaseline {
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+/// Stage times of an earlier benchmark run.
+///
+/// Stored as one line per day: the day number followed by the mean and
+/// standard deviation of the parse, part 1, part 2 and total times in
+/// nanoseconds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Baseline {
+    pub stages: BTreeMap<u32, [StageBaseline; 4]>,
+}
+
+impl Baseline {
+    pub fn from_benches(benches: &[Bench]) -> Baseline {
+        Baseline {
+            stages: benches.iter()
+                .map(|b| (b.day, b.stages.map(|s| StageBaseline { mean: s.mean, stddev: s.stddev })))
+                .collect(),
+        }
+    }
+
+    /// Load a baseline from `path`, which must exist.
+    pub fn load(path: &Path) -> Result<Baseline> {
+        Baseline::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Load a baseline from `path`. A missing file has no days.
+    pub fn load_or_default(path: &Path) -> Result<Baseline> {
+        match Baseline::load(path) {
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(Baseline::default()),
+            result => result,
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Baseline> {
+        let mut stages = BTreeMap::new();
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let numbers: Vec<u64> = line.split_whitespace()
+                .map(|n| n.parse())
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|_| Error::Validation(format!("line {}: expected only numbers", i + 1)))?;
+            let (day, times) = match numbers.split_first() {
+                Some((day, times)) if times.len() == 8 => (day, times),
+                _ => return Err(Error::Validation(format!(
+                    "line {}: expected a day and four times, each with its standard deviation", i + 1))),
+            };
+            let day = u32::try_from(*day)
+                .map_err(|_| Error::Validation(format!("line {}: day {} is out of range", i + 1, day)))?;
+            let nanos = |i: usize| Duration::from_nanos(times[i]);
+            stages.insert(day, [0, 1, 2, 3].map(|i| StageBaseline { mean: nanos(2 * i), stddev: nanos(2 * i + 1) }));
+        }
+        Ok(Baseline { stages })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::from("# day parse_ns parse_sd_ns part1_ns part1_sd_ns part2_ns part2_sd_ns total_ns total_sd_ns\n");
+        for (day, stages) in &self.stages {
+            write!(out, "{}", day).unwrap();
+            for s in stages {
+                write!(out, " {} {}", s.mean.as_nanos(), s.stddev.as_nanos()).unwrap();
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+}
+
+/// How a stage's mean changed against the baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub day: u32,
+    pub stage: &'static str,
+    pub before: Duration,
+    pub after: Duration,
+    /// Relative change in percent; positive is slower.
+    pub percent: f64,
+    pub regression: bool,
+}
+
+/// Compare every stage of `benches` present in `baseline`. A stage is a
+/// regression when its mean is more than `threshold` percent slower and the
+/// slowdown is also more than [`NOISE_SIGMAS`] times the combined standard
+/// deviation of both runs.
+pub fn compare(benches: &[Bench], baseline: &Baseline, threshold: f64) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for b in benches {
+        let stages = match baseline.stages.get(&b.day) {
+            Some(s) => s,
+            None => continue,
+        };
+        for (i, stage) in STAGES.iter().enumerate() {
+            let (before, after) = (stages[i].mean, b.stages[i].mean);
+            let percent = if before.is_zero() {
+                0.0
+            } else {
+                (after.as_secs_f64() / before.as_secs_f64() - 1.0) * 100.0
+            };
+            let noise = stages[i].stddev.as_secs_f64().hypot(b.stages[i].stddev.as_secs_f64());
+            let slowdown = after.as_secs_f64() - before.as_secs_f64();
+            let regression = percent > threshold && slowdown > NOISE_SIGMAS * noise;
+            changes.push(Change { day: b.day, stage, before, after, percent, regression });
+        }
+    }
+    changes
+}
+
+/// Render the changes, marking regressions.
+pub fn comparison_table(changes: &[Change]) -> String {
+    let mut out = String::new();
+    writeln!(out, "{:<6} {:<6} {:>12} {:>12} {:>9}", "Day", "Stage", "Baseline", "Now", "Change").unwrap();
+    for c in changes {
+        writeln!(out, "{:<6} {:<6} {:>12} {:>12} {:>+8.1}%{}",
+                 format!("{:02}", c.day), c.stage,
+                 format_duration(c.before), format_duration(c.after), c.percent,
+                 if c.regression { " REGRESSION" } else { "" }).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_day;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn statistics() {
+        let stats = Stats::of(&[ms(2), ms(4), ms(4), ms(4), ms(5), ms(5), ms(7), ms(9)]);
+        assert_eq!(stats.mean, ms(5));
+        assert_eq!(stats.median, ms(9) / 2);
+        assert_eq!(stats.stddev, ms(2));
+        assert_eq!(Stats::of(&[ms(3), ms(1), ms(2)]).median, ms(2));
+        assert_eq!(Stats::of(&[]), Stats::default());
+    }
+
+    #[test]
+    fn throughput() {
+        assert_eq!(format_throughput(512.0), "512.00 B/s");
+        assert_eq!(format_throughput(3.0 * 1024.0 * 1024.0), "3.00 MiB/s");
+    }
+
+    #[test]
+    fn baseline_round_trip_and_compare() {
+        let bench = |total| Bench {
+            day: 3,
+            input_bytes: 100,
+            iterations: 1,
+            stages: [Stats { mean: ms(1), ..Stats::default() }, Stats::default(), Stats::default(),
+                     Stats { mean: total, ..Stats::default() }],
+        };
+        let baseline = Baseline::from_benches(&[bench(ms(10))]);
+        assert_eq!(Baseline::parse(&baseline.to_text()).unwrap(), baseline);
+        assert!(Baseline::parse("3 1 2").is_err());
+        assert!(Baseline::parse("3 1000000 0 0 10000000").is_err());
+        assert!(matches!(Baseline::parse("4294967296 1 0 2 0 3 0 4 0"), Err(Error::Validation(_))));
+
+        let changes = compare(&[bench(ms(12))], &baseline, 10.0);
+        assert_eq!(changes.len(), 4);
+        assert!(!changes[0].regression);
+        assert!(changes[3].regression);
+        assert!((changes[3].percent - 20.0).abs() < 1e-9);
+        assert!(compare(&[bench(ms(12))], &baseline, 25.0).iter().all(|c| !c.regression));
+    }
+
+    #[test]
+    fn noisy_stages_are_not_regressions() {
+        let bench = |mean, stddev| Bench {
+            day: 3,
+            input_bytes: 100,
+            iterations: 10,
+            stages: [Stats { mean, stddev, ..Stats::default() }; 4],
+        };
+        // 20% slower, but within two combined standard deviations of 1.4ms.
+        let baseline = Baseline::from_benches(&[bench(ms(10), ms(1))]);
+        assert!(compare(&[bench(ms(12), ms(1))], &baseline, 10.0).iter().all(|c| !c.regression));
+        // The same slowdown with a tight spread is flagged.
+        let baseline = Baseline::from_benches(&[bench(ms(10), Duration::from_micros(100))]);
+        assert!(compare(&[bench(ms(12), Duration::from_micros(100))], &baseline, 10.0).iter().all(|c| c.regression));
+    }
+
+    #[test]
+    fn missing_baselines() {
+        let path = std::env::temp_dir().join(format!("aoc-missing-baseline-{}.txt", std::process::id()));
+        assert!(matches!(Baseline::load(&path), Err(Error::Io(_))));
+        assert_eq!(Baseline::load_or_default(&path).unwrap(), Baseline::default());
+    }
+
+    #[test]
+    fn bench_registered_day() {
+        let b = bench_day(find_day(2).unwrap(), "1-3 a: abcde\n", 3).unwrap();
+        assert_eq!((b.day, b.input_bytes, b.iterations), (2, 13, 3));
+    }
+}
@@ -0,0 +1,153 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::error::{Error, Result};
+use super::http;
+
+pub const YEAR: u32 = 2020;
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+/// Environment variable overriding the base URL, e.g. to point at a local server.
+pub const BASE_URL_VAR: &str = "AOC_BASE_URL";
+/// Environment variable holding the session cookie.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+/// File holding the session cookie, looked up in the working directory and
+/// then in the home directory.
+pub const SESSION_FILE: &str = ".aoc-session";
+
+const USER_AGENT: &str = "github.com/petermcevoy/adventofcode2020";
+
+/// A client for the puzzle website, authenticated with a session cookie.
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+    session: Option<String>,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: Option<String>) -> Client {
+        Client {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session,
+        }
+    }
+
+    /// A client using [`BASE_URL_VAR`] and the session from [`find_session`].
+    pub fn from_env() -> Client {
+        let base_url = env::var(BASE_URL_VAR).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Client::new(&base_url, find_session())
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn day_url(&self, day: u32) -> String {
+        format!("{}/{}/day/{}", self.base_url, YEAR, day)
+    }
+
+    fn session_cookie(&self) -> Result<String> {
+        let session = self.session.as_ref().ok_or_else(|| Error::Http(format!(
+            "no session token, set {} or create {}", SESSION_VAR, SESSION_FILE)))?;
+        Ok(format!("session={}", session))
+    }
+
+    /// Send an authenticated request and return the body of a successful
+    /// response.
+    pub(crate) fn request(&self, method: &str, url: &str, body: Option<&str>) -> Result<String> {
+        let cookie = self.session_cookie()?;
+        let mut headers = vec![("Cookie", cookie.as_str()), ("User-Agent", USER_AGENT)];
+        if body.is_some() {
+            headers.push(("Content-Type", "application/x-www-form-urlencoded"));
+        }
+        let response = http::request(method, url, &headers, body)?;
+        if !response.is_success() {
+            return Err(Error::Http(format!("{} returned status {}: {}",
+                url, response.status, response.body.lines().next().unwrap_or(""))));
+        }
+        Ok(response.body)
+    }
+
+    /// Download the puzzle input of `day`.
+    pub fn fetch_input(&self, day: u32) -> Result<String> {
+        self.request("GET", &format!("{}/input", self.day_url(day)), None)
+    }
+}
+
+/// The session token from [`SESSION_VAR`] or a [`SESSION_FILE`].
+pub fn find_session() -> Option<String> {
+    if let Ok(session) = env::var(SESSION_VAR) {
+        return Some(session.trim().to_string());
+    }
+    let mut candidates = vec![PathBuf::from(SESSION_FILE)];
+    if let Some(home) = env::var_os("HOME") {
+        candidates.push(Path::new(&home).join(SESSION_FILE));
+    }
+    candidates.iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|s| s.trim().to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fetched {
+    /// The input was already on disk and was not downloaded again.
+    Cached,
+    Downloaded,
+}
+
+/// Download the input of `day` to `path`, unless the file already exists.
+/// An empty file, like the placeholder made for a new day, is replaced.
+pub fn fetch_cached(client: &Client, day: u32, path: &Path) -> Result<Fetched> {
+    if fs::metadata(path).is_ok_and(|m| m.len() > 0) {
+        return Ok(Fetched::Cached);
+    }
+
+    let input = client.fetch_input(day)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Write to a temporary file first so an interrupted download never
+    // leaves a partial input behind to be mistaken for a cached one.
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, input)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(Fetched::Downloaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock;
+
+    #[test]
+    fn fetch_downloads_once() {
+        let (base_url, server) = mock::serve(vec![(200, String::from("1721\n979\n"))]);
+        let client = Client::new(&base_url, Some(String::from("abc")));
+        let dir = env::temp_dir().join(format!("aoc2020-fetch-{}", std::process::id()));
+        let path = dir.join("day01.txt");
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(fetch_cached(&client, 1, &path).unwrap(), Fetched::Downloaded);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1721\n979\n");
+        // The mock server only answers once, so this must not connect.
+        assert_eq!(fetch_cached(&client, 1, &path).unwrap(), Fetched::Cached);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /2020/day/1/input HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Cookie: session=abc\r\n"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_errors() {
+        let (base_url, server) = mock::serve(vec![(400, String::from("Please log in."))]);
+        let client = Client::new(&base_url, Some(String::from("expired")));
+        let path = env::temp_dir().join(format!("aoc2020-fetch-err-{}/day02.txt", std::process::id()));
+        assert!(matches!(fetch_cached(&client, 2, &path), Err(Error::Http(_))));
+        assert!(!path.exists());
+        server.join().unwrap();
+
+        let client = Client::new(&base_url, None);
+        assert!(matches!(client.fetch_input(2), Err(Error::Http(_))));
+    }
+}
@@ -1,15 +1,237 @@
+use std::fmt;
 use std::fs::File;
 use std::path::Path;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
 
-pub fn reader_as_string<R: BufRead>(reader: R) -> String {
+use super::diagnostic::Diagnostic;
+use super::error::Error;
+
+/// Read all lines from `reader`, joined by `\n` without a trailing newline.
+pub fn reader_as_string<R: BufRead>(reader: R) -> io::Result<String> {
     let input_vec: Vec<String> = reader.lines()
-        .collect::<Result<_, _>>().unwrap();
-    input_vec.join("\n")
+        .collect::<Result<_, _>>()?;
+    Ok(input_vec.join("\n"))
 }
 
-pub fn file_as_string(file_path: &Path) -> String {
-    let file = File::open(file_path).expect("Could not open file");
+/// Read the file at `file_path`, see [`reader_as_string`].
+pub fn file_as_string(file_path: &Path) -> io::Result<String> {
+    let file = File::open(file_path)?;
     let reader = BufReader::new(&file);
     reader_as_string(reader)
 }
+
+/// Open puzzle input for reading line by line: the file at `path`, or stdin
+/// if `path` is `-`.
+pub fn open_input(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if path == Path::new("-") {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+/// Read puzzle input from the file at `path`, or from stdin if `path` is `-`.
+pub fn read_input(path: &Path) -> io::Result<String> {
+    if path == Path::new("-") {
+        reader_as_string(io::stdin().lock())
+    } else {
+        file_as_string(path)
+    }
+}
+
+/// Which cells count as neighbours in a [`Grid`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Connectivity {
+    /// Up, down, left and right.
+    Four,
+    /// Including the diagonals.
+    Eight,
+}
+
+const OFFSETS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const OFFSETS_8: [(isize, isize); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1, 0), (1, 0),
+    (-1, 1), (0, 1), (1, 1),
+];
+
+/// A rectangular grid of tiles, stored row by row. `x` is the column and
+/// `y` the row, both counted from the top-left corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: usize, cols: usize, fill: T) -> Grid<T> where T: Clone {
+        Grid { rows, cols, cells: vec![fill; rows * cols] }
+    }
+
+    /// Parse one row per line, mapping each character to a tile with
+    /// `to_tile`. Every row must have the same number of columns.
+    pub fn parse<F: Fn(char) -> Option<T>>(input: &str, to_tile: F) -> Result<Grid<T>, Error> {
+        let rows = input.lines().count();
+        let cols = input.lines().next().map_or(0, |line| line.chars().count());
+        let mut cells = Vec::with_capacity(rows * cols);
+
+        for (i_line, line) in input.lines().enumerate() {
+            let line_cols = line.chars().count();
+            if line_cols != cols {
+                let columns = (cols.min(line_cols) + 1)..(cols.max(line_cols) + 1);
+                return Err(Diagnostic::new(i_line + 1, line, columns,
+                    format!("expected {} columns, found {}", cols, line_cols)).into());
+            }
+
+            for (i_c, c) in line.chars().enumerate() {
+                let tile = to_tile(c).ok_or_else(|| {
+                    Diagnostic::at(i_line + 1, line, i_c + 1, format!("unexpected character '{}'", c))
+                })?;
+                cells.push(tile);
+            }
+        }
+
+        Ok(Grid { rows, cols, cells })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.cols && y < self.rows {
+            Some(&self.cells[y * self.cols + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.cols && y < self.rows {
+            Some(&mut self.cells[y * self.cols + x])
+        } else {
+            None
+        }
+    }
+
+    /// The tile at `(x, y)` on an infinite tiling of the grid. Panics if the
+    /// grid is empty.
+    pub fn get_wrapping(&self, x: isize, y: isize) -> &T {
+        let x = x.rem_euclid(self.cols as isize) as usize;
+        let y = y.rem_euclid(self.rows as isize) as usize;
+        &self.cells[y * self.cols + x]
+    }
+
+    /// The in-bounds neighbours of `(x, y)` as `(x, y, tile)`.
+    pub fn neighbours(&self, x: usize, y: usize, connectivity: Connectivity)
+        -> impl Iterator<Item = (usize, usize, &T)> + '_ {
+        let offsets: &[(isize, isize)] = match connectivity {
+            Connectivity::Four => &OFFSETS_4,
+            Connectivity::Eight => &OFFSETS_8,
+        };
+        offsets.iter().filter_map(move |&(dx, dy)| {
+            let nx = x.checked_add_signed(dx)?;
+            let ny = y.checked_add_signed(dy)?;
+            self.get(nx, ny).map(|tile| (nx, ny, tile))
+        })
+    }
+
+    /// The tiles of row `y`.
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.cols..(y + 1) * self.cols]
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.rows).map(move |y| self.row(y))
+    }
+
+    /// The tiles of column `x`, top to bottom.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(x < self.cols, "column {} out of bounds", x);
+        self.cells.iter().skip(x).step_by(self.cols)
+    }
+
+    /// Every tile as `(x, y, tile)`, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> + '_ {
+        self.cells.iter().enumerate().map(move |(i, tile)| (i % self.cols, i / self.cols, tile))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.iter_rows().enumerate() {
+            if y > 0 { writeln!(f)?; }
+            for tile in row {
+                write!(f, "{}", tile)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+.#.
+##.";
+
+    fn example() -> Grid<bool> {
+        Grid::parse(EXAMPLE, |c| match c {
+            '.' => Some(false),
+            '#' => Some(true),
+            _ => None,
+        }).unwrap()
+    }
+
+    #[test]
+    fn parse_and_access() {
+        let grid = example();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid.get(1, 0), Some(&true));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+        assert_eq!(grid.get_wrapping(3, 1), &true);
+        assert_eq!(grid.get_wrapping(-1, -1), &false);
+        assert_eq!(grid.row(1), &[true, true, false]);
+        assert_eq!(grid.column(0).collect::<Vec<_>>(), vec![&false, &true]);
+        assert_eq!(grid.iter().filter(|(_, _, &t)| t).count(), 3);
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = example();
+        let n4: Vec<_> = grid.neighbours(0, 0, Connectivity::Four).map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(n4, vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbours(1, 1, Connectivity::Eight).count(), 5);
+        assert_eq!(grid.neighbours(1, 0, Connectivity::Eight).filter(|(_, _, &t)| t).count(), 2);
+    }
+
+    #[test]
+    fn display() {
+        let grid = example();
+        let rendered = Grid {
+            rows: grid.rows(),
+            cols: grid.cols(),
+            cells: grid.cells.iter().map(|&t| if t { '#' } else { '.' }).collect(),
+        }.to_string();
+        assert_eq!(rendered, EXAMPLE);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = Grid::parse(".#\n.x", |c| if c == '.' { Some(()) } else { None }).unwrap_err();
+        assert!(matches!(err, Error::Parse(d) if d.line == 1 && d.column() == 2));
+        assert!(Grid::parse("..\n...", |_| Some(())).is_err());
+    }
+}
@@ -1,51 +1,525 @@
-use std::fs::File;
-use std::path::Path;
-use std::io::{BufRead, BufReader};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+use super::diagnostic::Diagnostic;
+use super::error::{Error, Result};
+use super::rng::Rng;
+use super::solution::{Answer, Solution};
 
-pub fn run(input_path: &Path) -> bool {
-    let file = File::open(input_path).expect("Could not open path");
-    let reader = BufReader::new(file);
-    let lines: Vec<u32> = reader.lines().map(|l| l.unwrap().parse::<u32>().unwrap() ).collect();
+/// Day 1: Report Repair.
+pub struct Day01;
+impl Solution for Day01 {
+    type Input = Vec<u64>;
 
-    print!("Part 1: ");
-    let _product_part1 = find_matches(&lines, 2020, 2);
-    print!("Part 2: ");
-    let _product_part2 = find_matches(&lines, 2020, 3);
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer> {
+        let product = find_matches(input, 2020, 2)?
+            .ok_or_else(|| Error::MissingAnswer(String::from("no two entries sum to 2020")))?;
+        Ok(product.into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer> {
+        let product = find_matches(input, 2020, 3)?
+            .ok_or_else(|| Error::MissingAnswer(String::from("no three entries sum to 2020")))?;
+        Ok(product.into())
+    }
+}
 
-    true
+/// An integer type the expense report can be solved in. All arithmetic is
+/// checked.
+pub trait Entry: Copy + Ord + Hash + fmt::Debug + fmt::Display {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn from_usize(n: usize) -> Option<Self>;
 }
 
-fn find_matches(lines: &[u32], sum_match: u32, num_terms: usize) -> Option<u32> {
-    let mut indices: Vec<usize> = vec![0; num_terms];
-    for (i, item) in indices.iter_mut().enumerate() { *item = i }
-    
-    loop {
-        let mut sum = 0;
-        for i in 0..num_terms { sum += lines[indices[i]]; }
-        if sum == sum_match { 
-            let mut product = 1;
-            for i in 0..num_terms { 
-                print!("{}", {lines[indices[i]]});
-                product *= lines[indices[i]];
-                if i < (num_terms - 1) { print!(" + "); }
+macro_rules! impl_entry {
+    ($($t:ty),*) => {
+        $(
+            impl Entry for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn checked_add(self, other: Self) -> Option<Self> { <$t>::checked_add(self, other) }
+                fn checked_sub(self, other: Self) -> Option<Self> { <$t>::checked_sub(self, other) }
+                fn checked_mul(self, other: Self) -> Option<Self> { <$t>::checked_mul(self, other) }
+                fn from_usize(n: usize) -> Option<Self> { Self::try_from(n).ok() }
             }
-            println!(" = {} => product = {}", sum, product);
-            return Some(product);
+        )*
+    };
+}
+impl_entry!(i32, u32, i64, u64, i128);
+
+/// Parse one entry per line.
+pub fn parse<T: Entry + FromStr>(input: &str) -> Result<Vec<T>>
+where
+    T::Err: fmt::Display,
+{
+    input.lines()
+        .enumerate()
+        .map(|(i_line, l)| {
+            l.parse::<T>().map_err(|e| Diagnostic::whole_line(i_line + 1, l, e.to_string()).into())
+        })
+        .collect()
+}
+
+fn overflow(what: String) -> Error {
+    Error::Overflow(what)
+}
+
+/// Find `num_terms` entries of `lines` that sum to `sum_match` and return
+/// their product.
+pub fn find_matches<T: Entry>(lines: &[T], sum_match: T, num_terms: usize) -> Result<Option<T>> {
+    match k_sum(lines, sum_match, num_terms)? {
+        Some(terms) => Ok(Some(product(&terms)?)),
+        None => Ok(None),
+    }
+}
+
+/// The product of `terms`, or an error if it does not fit `T`.
+pub fn product<T: Entry>(terms: &[T]) -> Result<T> {
+    terms.iter()
+        .try_fold(T::ONE, |p, &t| p.checked_mul(t))
+        .ok_or_else(|| overflow(format!("the product of {:?} does not fit", terms)))
+}
+
+/// Compare `a + b` with `target`, even when the sum does not fit `T`.
+fn cmp_sum<T: Entry>(a: T, b: T, target: T) -> Ordering {
+    match a.checked_add(b) {
+        Some(sum) => sum.cmp(&target),
+        // Only a positive `b` can overflow past the maximum.
+        None if b > T::ZERO => Ordering::Greater,
+        None => Ordering::Less,
+    }
+}
+
+/// Whether `count` terms of at least `value` each must sum past `target`.
+fn exceeds<T: Entry>(value: T, count: usize, target: T) -> bool {
+    match T::from_usize(count).and_then(|c| value.checked_mul(c)) {
+        Some(bound) => bound > target,
+        None => value > T::ZERO,
+    }
+}
+
+/// Find `k` entries of `values` that sum to `target`, each entry used at
+/// most once, and return them.
+///
+/// Pairs are found with a hash set in O(n), triples with two pointers over
+/// the sorted values in O(n^2), and larger `k` by fixing one term at a time
+/// until three are left. Fails if an intermediate sum does not fit `T`.
+pub fn k_sum<T: Entry>(values: &[T], target: T, k: usize) -> Result<Option<Vec<T>>> {
+    Ok(match k {
+        0 => if target == T::ZERO { Some(Vec::new()) } else { None },
+        1 => values.iter().find(|&&v| v == target).map(|&v| vec![v]),
+        2 => two_sum(values, target),
+        _ => {
+            let mut sorted = values.to_vec();
+            sorted.sort_unstable();
+            k_sum_sorted(&sorted, target, k)?
         }
+    })
+}
 
-        // Increment last index
-        indices[num_terms - 1] += 1;
-        
-        // Check overflows.
-        for i in (1..num_terms).rev() {
-            if indices[i] == lines.len() {
-                indices[i - 1] += 1;
-                indices[i] = (indices[i - 1] + 1).min(lines.len()-1);
+fn two_sum<T: Entry>(values: &[T], target: T) -> Option<Vec<T>> {
+    let mut seen = HashSet::new();
+    for &v in values {
+        // A complement that does not fit `T` cannot be among the values.
+        if let Some(complement) = target.checked_sub(v) {
+            if seen.contains(&complement) {
+                return Some(vec![complement, v]);
             }
         }
-        
-        if indices[0] == (lines.len() - 1) {break;}
+        seen.insert(v);
     }
+    None
+}
 
+/// `k_sum` for `k >= 3` on sorted values.
+fn k_sum_sorted<T: Entry>(sorted: &[T], target: T, k: usize) -> Result<Option<Vec<T>>> {
+    if sorted.len() < k {
+        return Ok(None);
+    }
+    for (i, &first) in sorted.iter().enumerate() {
+        // Values are sorted, so every later term is at least as large.
+        if exceeds(first, k, target) {
+            break;
+        }
+        if i > 0 && sorted[i - 1] == first {
+            continue;
+        }
+        let rest = &sorted[i + 1..];
+        let remaining = target.checked_sub(first)
+            .ok_or_else(|| overflow(format!("{} - {} does not fit", target, first)))?;
+        let found = if k == 3 {
+            two_pointer(rest, remaining)
+        } else {
+            k_sum_sorted(rest, remaining, k - 1)?
+        };
+        if let Some(mut terms) = found {
+            terms.insert(0, first);
+            return Ok(Some(terms));
+        }
+    }
+    Ok(None)
+}
+
+/// Find two entries of sorted `values` summing to `target`.
+fn two_pointer<T: Entry>(sorted: &[T], target: T) -> Option<Vec<T>> {
+    if sorted.len() < 2 {
+        return None;
+    }
+    let (mut lo, mut hi) = (0, sorted.len() - 1);
+    while lo < hi {
+        match cmp_sum(sorted[lo], sorted[hi], target) {
+            Ordering::Equal => return Some(vec![sorted[lo], sorted[hi]]),
+            Ordering::Less => lo += 1,
+            Ordering::Greater => hi -= 1,
+        }
+    }
     None
 }
+
+/// Every distinct combination of `k` entries of `values` summing to
+/// `target`, as sorted terms. Equal entries can be combined, but no
+/// combination uses a value more often than it appears in `values`.
+///
+/// Yields an error, and then stops, if a partial sum does not fit `T`.
+pub fn k_sum_all<T: Entry>(values: &[T], target: T, k: usize) -> KSumCombinations<T> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    KSumCombinations {
+        sorted,
+        target,
+        k,
+        indices: Vec::new(),
+        sum: T::ZERO,
+        next: 0,
+        done: false,
+    }
+}
+
+/// Iterator returned by [`k_sum_all`].
+///
+/// Walks the sorted values depth first, choosing one index per term and
+/// looking the last term up with a binary search.
+#[derive(Debug, Clone)]
+pub struct KSumCombinations<T> {
+    sorted: Vec<T>,
+    target: T,
+    k: usize,
+    /// Indices of the terms chosen so far.
+    indices: Vec<usize>,
+    sum: T,
+    /// The first index the next term may use.
+    next: usize,
+    done: bool,
+}
+
+impl<T: Entry> KSumCombinations<T> {
+    /// Drop the last chosen term and continue after all values equal to it.
+    fn backtrack(&mut self) {
+        match self.indices.pop() {
+            Some(i) => {
+                let value = self.sorted[i];
+                // Removing a term that was just added always fits.
+                self.sum = self.sum.checked_sub(value).unwrap();
+                self.next = i + self.sorted[i..].iter().take_while(|&&v| v == value).count();
+            },
+            None => self.done = true,
+        }
+    }
+}
+
+impl<T: Entry> Iterator for KSumCombinations<T> {
+    type Item = Result<Vec<T>>;
+
+    fn next(&mut self) -> Option<Result<Vec<T>>> {
+        if self.k == 0 {
+            let found = !self.done && self.target == T::ZERO;
+            self.done = true;
+            return if found { Some(Ok(Vec::new())) } else { None };
+        }
+        while !self.done {
+            let remaining_terms = self.k - self.indices.len();
+            // A remainder that does not fit `T` cannot be reached by values
+            // of `T`, and gives no bound to prune with.
+            let remainder = self.target.checked_sub(self.sum);
+            if remaining_terms == 1 {
+                let rest = &self.sorted[self.next..];
+                let terms = remainder
+                    .filter(|need| rest.binary_search(need).is_ok())
+                    .map(|need| {
+                        let mut terms: Vec<T> = self.indices.iter().map(|&i| self.sorted[i]).collect();
+                        terms.push(need);
+                        terms
+                    });
+                self.backtrack();
+                if let Some(terms) = terms {
+                    return Some(Ok(terms));
+                }
+                continue;
+            }
+
+            let i = self.next;
+            if i >= self.sorted.len() || remainder.is_some_and(|r| exceeds(self.sorted[i], remaining_terms, r)) {
+                self.backtrack();
+                continue;
+            }
+            match self.sum.checked_add(self.sorted[i]) {
+                Some(sum) => self.sum = sum,
+                None => {
+                    self.done = true;
+                    return Some(Err(overflow(format!("{} + {} does not fit", self.sum, self.sorted[i]))));
+                }
+            }
+            self.indices.push(i);
+            self.next = i + 1;
+        }
+        None
+    }
+}
+
+/// The number of combinations [`k_sum_all`] yields, without building them.
+/// The last two terms are counted with two pointers rather than enumerated.
+pub fn count_k_sum<T: Entry>(values: &[T], target: T, k: usize) -> Result<u64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    count_sorted(&sorted, target, k)
+}
+
+fn count_sorted<T: Entry>(sorted: &[T], target: T, k: usize) -> Result<u64> {
+    Ok(match k {
+        0 => (target == T::ZERO) as u64,
+        1 => sorted.binary_search(&target).is_ok() as u64,
+        2 => count_pairs(sorted, target),
+        _ => {
+            let mut count = 0;
+            for (i, &first) in sorted.iter().enumerate() {
+                if exceeds(first, k, target) {
+                    break;
+                }
+                if i > 0 && sorted[i - 1] == first {
+                    continue;
+                }
+                let remaining = target.checked_sub(first)
+                    .ok_or_else(|| overflow(format!("{} - {} does not fit", target, first)))?;
+                count += count_sorted(&sorted[i + 1..], remaining, k - 1)?;
+            }
+            count
+        }
+    })
+}
+
+/// Count the distinct pairs of sorted values summing to `target`.
+fn count_pairs<T: Entry>(sorted: &[T], target: T) -> u64 {
+    if sorted.len() < 2 {
+        return 0;
+    }
+    let (mut lo, mut hi) = (0, sorted.len() - 1);
+    let mut count = 0;
+    while lo < hi {
+        match cmp_sum(sorted[lo], sorted[hi], target) {
+            Ordering::Less => lo += 1,
+            Ordering::Greater => hi -= 1,
+            Ordering::Equal => {
+                count += 1;
+                let (low, high) = (sorted[lo], sorted[hi]);
+                while lo < hi && sorted[lo] == low { lo += 1; }
+                while hi > lo && sorted[hi] == high { hi -= 1; }
+            }
+        }
+    }
+    count
+}
+
+/// A generated expense report with exactly one pair and one triple of
+/// entries summing to the target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generated {
+    pub entries: Vec<u64>,
+    pub pair: [u64; 2],
+    pub triple: [u64; 3],
+}
+
+impl Generated {
+    /// The entries, one per line.
+    pub fn input(&self) -> String {
+        self.entries.iter().map(|e| format!("{}\n", e)).collect()
+    }
+
+    /// The expected answers in the format of an example sidecar.
+    pub fn answers(&self) -> Result<String> {
+        Ok(format!("part1 = {}\npart2 = {}\n", product(&self.pair)?, product(&self.triple)?))
+    }
+}
+
+/// Generate `count` entries from `seed` with exactly one pair and exactly
+/// one triple summing to `target`.
+///
+/// The planted terms are all below `target / 2`, except the larger half of
+/// the pair. Every other entry lies above `target / 2`, so it can only reach
+/// the target together with planted terms, and the values that would are
+/// never drawn.
+pub fn generate(seed: u64, count: usize, target: u64) -> Result<Generated> {
+    if count < 5 {
+        return Err(Error::Validation(String::from("at least 5 entries are needed")));
+    }
+    if target < 40 {
+        return Err(Error::Validation(String::from("the target must be at least 40")));
+    }
+
+    let mut rng = Rng::new(seed);
+    let half = target / 2;
+    let (pair, triple) = loop {
+        let p = rng.range(1, half);
+        let a = rng.range(target / 4 + 1, half);
+        let b = rng.range(target / 4 + 1, half);
+        let planted = [p, target - p, a, b, target - a - b];
+        if count_k_sum(&planted, target, 2)? == 1 && count_k_sum(&planted, target, 3)? == 1 {
+            break ([p, target - p], [a, b, target - a - b]);
+        }
+    };
+
+    let small = [pair[0], triple[0], triple[1], triple[2]];
+    let mut forbidden: HashSet<u64> = small.iter().map(|s| target - s).collect();
+    for (i, s) in small.iter().enumerate() {
+        for t in &small[i + 1..] {
+            forbidden.insert(target - s - t);
+        }
+    }
+
+    let mut entries: Vec<u64> = pair.iter().chain(&triple).copied().collect();
+    while entries.len() < count {
+        let filler = rng.range(half + 1, target);
+        if !forbidden.contains(&filler) {
+            entries.push(filler);
+        }
+    }
+    rng.shuffle(&mut entries);
+
+    if count_k_sum(&entries, target, 2)? != 1 || count_k_sum(&entries, target, 3)? != 1 {
+        return Err(Error::Validation(format!("seed {} did not give a unique solution", seed)));
+    }
+    Ok(Generated { entries, pair, triple })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [u32; 6] = [1721, 979, 366, 299, 675, 1456];
+
+    fn sorted<T: Entry>(terms: Result<Option<Vec<T>>>) -> Option<Vec<T>> {
+        terms.unwrap().map(|mut t| { t.sort_unstable(); t })
+    }
+
+    fn all<T: Entry>(combinations: KSumCombinations<T>) -> Vec<Vec<T>> {
+        combinations.collect::<Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(sorted(k_sum(&EXAMPLE, 2020, 2)), Some(vec![299, 1721]));
+        assert_eq!(sorted(k_sum(&EXAMPLE, 2020, 3)), Some(vec![366, 675, 979]));
+        assert_eq!(find_matches(&EXAMPLE, 2020, 2).unwrap(), Some(514579));
+        assert_eq!(find_matches(&EXAMPLE, 2020, 3).unwrap(), Some(241861950));
+    }
+
+    #[test]
+    fn larger_k() {
+        assert_eq!(sorted(k_sum(&EXAMPLE, 299 + 366 + 675 + 1456, 4)), Some(vec![299, 366, 675, 1456]));
+        assert_eq!(sorted(k_sum(&[1, 2, 3, 4, 5, 6], 21, 6)), Some(vec![1, 2, 3, 4, 5, 6]));
+        assert_eq!(sorted(k_sum(&[1, 2, 3, 4, 5, 6], 22, 6)), None);
+    }
+
+    #[test]
+    fn all_combinations() {
+        assert_eq!(all(k_sum_all(&EXAMPLE, 2020, 3)), vec![vec![366, 675, 979]]);
+
+        let values = [1, 1, 2, 3, 3, 4, 5];
+        assert_eq!(all(k_sum_all(&values, 6, 2)), vec![vec![1, 5], vec![2, 4], vec![3, 3]]);
+        assert_eq!(all(k_sum_all(&values, 7, 3)), vec![vec![1, 1, 5], vec![1, 2, 4], vec![1, 3, 3]]);
+        assert_eq!(k_sum_all(&[3], 6, 2).count(), 0);
+        assert_eq!(all(k_sum_all::<u32>(&[], 0, 0)), vec![Vec::<u32>::new()]);
+        assert_eq!(k_sum_all(&[u32::MAX, 1], 0, 1).count(), 0);
+
+        assert_eq!(product(&[366, 675, 979]).unwrap(), 241861950);
+        assert!(matches!(product(&[u32::MAX, u32::MAX, 2]), Err(Error::Overflow(_))));
+    }
+
+    #[test]
+    fn counting_matches_enumeration() {
+        let values: Vec<u32> = (0..40).map(|i| (i * 7 + 3) % 23).collect();
+        for k in 0..=5 {
+            for target in [0, 10, 23, 40, 61] {
+                assert_eq!(count_k_sum(&values, target, k).unwrap(), k_sum_all(&values, target, k).count() as u64,
+                           "k = {}, target = {}", k, target);
+            }
+        }
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert_eq!(sorted(k_sum::<u32>(&[], 2020, 2)), None);
+        assert_eq!(sorted(k_sum::<u32>(&[], 2020, 3)), None);
+        assert_eq!(sorted(k_sum(&[2020], 2020, 1)), Some(vec![2020]));
+        assert_eq!(sorted(k_sum(&[1, 2], 0, 0)), Some(vec![]));
+        // An entry cannot be used twice, but equal entries can be combined.
+        assert_eq!(sorted(k_sum(&[1010], 2020, 2)), None);
+        assert_eq!(sorted(k_sum(&[1010, 1010], 2020, 2)), Some(vec![1010, 1010]));
+        assert_eq!(sorted(k_sum(&[5, 5, 5], 15, 3)), Some(vec![5, 5, 5]));
+        assert_eq!(sorted(k_sum(&[u32::MAX, u32::MAX, 1], 1, 3)), None);
+    }
+
+    #[test]
+    fn signed_and_wide() {
+        let values: [i64; 6] = [-5, 10, 3, -2, 7, 0];
+        assert_eq!(sorted(k_sum(&values, 5, 2)), Some(vec![-5, 10]));
+        assert_eq!(sorted(k_sum(&values, 0, 3)), Some(vec![-5, -2, 7]));
+        assert_eq!(find_matches(&values, -7, 2).unwrap(), Some(10));
+        assert_eq!(all(k_sum_all(&values, 5, 3)), vec![vec![-5, 0, 10], vec![-5, 3, 7], vec![-2, 0, 7]]);
+        assert_eq!(count_k_sum(&values, 5, 3).unwrap(), 3);
+
+        let big = [i128::MAX - 1, 1, i128::MIN];
+        assert_eq!(sorted(k_sum(&big, i128::MAX, 2)), Some(vec![1, i128::MAX - 1]));
+        assert_eq!(sorted(k_sum(&[u64::MAX, u64::MAX], u64::MAX, 2)), None);
+    }
+
+    #[test]
+    fn generated_inputs() {
+        for seed in 0..20 {
+            let generated = generate(seed, 200, 2020).unwrap();
+            assert_eq!(generated.entries.len(), 200);
+            assert_eq!(count_k_sum(&generated.entries, 2020, 2).unwrap(), 1);
+            assert_eq!(count_k_sum(&generated.entries, 2020, 3).unwrap(), 1);
+
+            let input = Day01::parse(&generated.input()).unwrap();
+            let expected = format!("part1 = {}\npart2 = {}\n",
+                                   Day01::part1(&input).unwrap(), Day01::part2(&input).unwrap());
+            assert_eq!(generated.answers().unwrap(), expected);
+        }
+        assert_eq!(generate(3, 50, 2020).unwrap(), generate(3, 50, 2020).unwrap());
+        assert!(generate(3, 4, 2020).is_err());
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert!(matches!(find_matches(&[1u64 << 40, 1 << 40], 1 << 41, 2), Err(Error::Overflow(_))));
+
+        // The terms sum to zero, but the two smallest already overflow.
+        let values = [-i64::MAX, -i64::MAX, i64::MAX, i64::MAX];
+        assert!(matches!(k_sum(&values, 0, 4), Err(Error::Overflow(_))));
+        assert!(matches!(k_sum_all(&values, 0, 4).next(), Some(Err(Error::Overflow(_)))));
+        assert!(matches!(count_k_sum(&values, 0, 4), Err(Error::Overflow(_))));
+    }
+}
@@ -1,87 +1,722 @@
-use std::fs::File;
-use std::path::Path;
-use std::io::{BufRead, BufReader, Seek};
-use std::io;
-use std::error::Error;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::BufRead;
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+use super::diagnostic::Diagnostic;
+use super::error::{Error, Result};
+use super::output::{csv_string, json_string, Format};
+use super::solution::{Answer, Solution};
 
-pub fn run(input_path: &Path) -> bool {
-    let records = match parse(input_path) {
-        Ok(v) => v,
-        Err(e) => {
-            eprintln!("There was a problem parsing the input file:");
-            eprintln!("{}", e);
-            return false;
-        }
-    };
+/// Day 2: Password Philosophy.
+pub struct Day02;
+impl Solution for Day02 {
+    type Input = Vec<PasswordRecord>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer> {
+        Ok(input.iter().filter(|r| check_policy1(r, Unit::Char)).count().into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer> {
+        Ok(input.iter().filter(|r| check_policy2(r, Unit::Char)).count().into())
+    }
+}
+
+/// One line of the password database: a policy and the password it applies to.
+#[derive(Debug)]
+pub struct PasswordRecord {
+    /// 1-based line number in the database.
+    pub line: usize,
+    pub range_start: usize,
+    pub range_end: usize,
+    pub charachter: char,
+    pub password: String
+}
 
-    let num_valid_part1 = records.iter().filter(|r| check_policy1(&r)).count();
-    println!("Part 1: num valid passwords: {}", num_valid_part1);
-    
-    let num_valid_part2 = records.iter().filter(|r| check_policy2(&r)).count();
-    println!("Part 2: num valid passwords: {}", num_valid_part2);
+fn parse_err<S: Into<String>>(line_no: usize, line: &str, part: &str, message: S) -> Error {
+    Diagnostic::spanning(line_no, line, part, format!("Could not parse record: {}", message.into())).into()
+}
+
+/// Parse one line of the database; `line_no` is 1-based.
+pub fn parse_record(line_no: usize, line: &str) -> Result<PasswordRecord> {
+    let end = &line[line.len()..];
+    let (policy_str, password_str) = line.split_once(':')
+        .ok_or_else(|| parse_err(line_no, line, end, "expected ':'"))?;
+
+    let (range_str, char_str) = policy_str.split_once(' ')
+        .ok_or_else(|| parse_err(line_no, line, policy_str, "expected a policy like '1-3 a'"))?;
+    let mut chars = char_str.chars();
+    let policy_char = chars.next()
+        .ok_or_else(|| parse_err(line_no, line, char_str, "expected a policy character"))?;
+    if !chars.as_str().is_empty() {
+        return Err(parse_err(line_no, line, chars.as_str(), "expected a single policy character"));
+    }
+    let (range_start_str, range_end_str) = range_str.split_once('-')
+        .ok_or_else(|| parse_err(line_no, line, range_str, "expected a range like '1-3'"))?;
+    let range_start = range_start_str.parse::<usize>()
+        .map_err(|e| parse_err(line_no, line, range_start_str, e.to_string()))?;
+    let range_end = range_end_str.parse::<usize>()
+        .map_err(|e| parse_err(line_no, line, range_end_str, e.to_string()))?;
+
+    Ok(PasswordRecord {
+        line: line_no,
+        range_start,
+        range_end,
+        charachter: policy_char,
+        password: String::from(password_str.trim())
+    })
+}
 
-    true
+/// Parse the password database, one record per line.
+pub fn parse(input: &str) -> Result<Vec<PasswordRecord>> {
+    records(input.as_bytes()).collect()
 }
 
+/// Lazily parse the records of `reader`, one line at a time, see [`Records`].
+pub fn records<R: BufRead>(reader: R) -> Records<R> {
+    Records { reader, line: Vec::new(), line_no: 0, done: false }
+}
+
+/// An iterator over the records of a reader that holds a single line in
+/// memory. Iteration stops after the first error; a line that is not UTF-8
+/// is a parse error.
 #[derive(Debug)]
-struct PasswordRecord {
-    range_start: usize,
-    range_end: usize,
-    charachter: char,
-    password: String
-}
-
-fn parse_err(e: Option<&dyn Error>, line: usize) -> io::Error {
-    let msg = match e {
-        Some(e) => format!(": {}", e),
-        None => "".to_string()
-    };
-    io::Error::new( io::ErrorKind::Other, format!("Could not parse line {}{}", line+1, msg))
-}
-
-fn parse(filepath: &Path) -> Result<Vec<PasswordRecord>, Box<dyn Error>> {
-    let mut file = File::open(filepath).expect("Could not open file");
-    let num_lines = BufReader::new(&file).lines().count();
-    file.seek(io::SeekFrom::Start(0))?;
-    
-    let mut records = Vec::<PasswordRecord>::with_capacity(num_lines);
-    let reader = BufReader::new(&file);
-    for (i_line, line) in reader.lines().enumerate() {
-        let line = line?;
-        let parts: Vec<&str> = line.split(':').collect();
-        let policy_str = parts[0];
-        let password_str = parts[1];
-
-        let policy_parts: Vec<&str> = policy_str.split(' ').collect();
-        let policy_char = policy_parts[1].chars().next()
-            .ok_or_else(|| parse_err(None, i_line))?;
-        let range_parts: Vec<&str> = policy_parts[0].split('-').collect();
-        let range_start = range_parts[0].parse::<usize>()
-            .map_err(|e| parse_err(Some(&e), i_line))?;
-        let range_end = range_parts[1].parse::<usize>()
-            .map_err(|e| parse_err(Some(&e), i_line))?;
-
-        records.push(PasswordRecord { 
-            range_start,
-            range_end,
-            charachter: policy_char,
-            password: String::from(password_str.trim())
-        });
-    }
-
-    Ok(records)
-}
-
-fn check_policy1(record: &PasswordRecord) -> bool {
-    let char_count = record.password.matches(record.charachter).count();
+pub struct Records<R> {
+    reader: R,
+    line: Vec<u8>,
+    line_no: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = Result<PasswordRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        self.line.clear();
+        match self.reader.read_until(b'\n', &mut self.line) {
+            Ok(0) => {
+                self.done = true;
+                return None;
+            },
+            Ok(_) => self.line_no += 1,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(Error::Io(e)));
+            },
+        }
+        let line = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let record = match std::str::from_utf8(line) {
+            Ok(line) => parse_record(self.line_no, line),
+            Err(e) => {
+                let valid = std::str::from_utf8(&line[..e.valid_up_to()]).unwrap();
+                Err(Diagnostic::at(self.line_no, &String::from_utf8_lossy(line), valid.chars().count() + 1,
+                                   "Could not parse record: invalid UTF-8").into())
+            },
+        };
+        self.done = record.is_err();
+        Some(record)
+    }
+}
+
+/// What the positions and counts of the policies refer to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    /// Bytes of the UTF-8 encoding; only an ASCII policy character matches.
+    Byte,
+    /// Unicode code points.
+    Char,
+    /// Extended grapheme clusters of Unicode Standard Annex #29; the policy
+    /// character only matches a cluster of that single character.
+    Grapheme,
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Unit, String> {
+        match s {
+            "byte" => Ok(Unit::Byte),
+            "char" => Ok(Unit::Char),
+            "grapheme" => Ok(Unit::Grapheme),
+            _ => Err(format!("unknown unit '{}', expected byte, char or grapheme", s)),
+        }
+    }
+}
+
+impl Unit {
+    /// The plural name of the unit.
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Byte => "bytes",
+            Unit::Char => "chars",
+            Unit::Grapheme => "graphemes",
+        }
+    }
+
+    /// Split `s` into units, each as the bytes encoding it.
+    pub fn split(self, s: &str) -> Box<dyn Iterator<Item = &[u8]> + '_> {
+        match self {
+            Unit::Byte => Box::new(s.as_bytes().chunks(1)),
+            Unit::Char => Box::new(s.char_indices().map(move |(i, c)| &s.as_bytes()[i..i + c.len_utf8()])),
+            Unit::Grapheme => Box::new(s.graphemes(true).map(str::as_bytes)),
+        }
+    }
+
+    /// The number of units of `s` that are the character `c`.
+    pub fn count(self, s: &str, c: char) -> usize {
+        match self {
+            Unit::Char => s.matches(c).count(),
+            _ => {
+                let mut buf = [0; 4];
+                let c = c.encode_utf8(&mut buf).as_bytes();
+                self.split(s).filter(|u| *u == c).count()
+            },
+        }
+    }
+
+    /// Whether the units at the (1-based) positions `p1` and `p2` of `s` are
+    /// the character `c`, in a single pass. `None` for a position out of range.
+    pub fn holds_at(self, s: &str, c: char, p1: usize, p2: usize) -> (Option<bool>, Option<bool>) {
+        let mut buf = [0; 4];
+        let c = c.encode_utf8(&mut buf).as_bytes();
+        let at = |units: &mut dyn Iterator<Item = &[u8]>, skip: usize| units.nth(skip).map(|u| u == c);
+
+        let (low, high) = (p1.min(p2), p1.max(p2));
+        if low == 0 {
+            let other = if high == 0 { None } else { at(&mut self.split(s), high - 1) };
+            return if p1 == 0 { (None, other) } else { (other, None) };
+        }
+        let mut units = self.split(s);
+        let first = at(&mut units, low - 1);
+        let second = if high == low { first } else { at(&mut units, high - low - 1) };
+        if p1 <= p2 { (first, second) } else { (second, first) }
+    }
+}
+
+/// The sled rental policy: the character occurs between `range_start` and
+/// `range_end` times.
+pub fn check_policy1(record: &PasswordRecord, unit: Unit) -> bool {
+    let char_count = unit.count(&record.password, record.charachter);
     char_count >= record.range_start && char_count <= record.range_end
 }
 
-fn check_policy2(record: &PasswordRecord) -> bool {
-    let i1 = record.range_start - 1;
-    let i2 = record.range_end - 1;
-    let char1 = record.password.chars().nth(i1).unwrap();
-    let char2 = record.password.chars().nth(i2).unwrap();
+/// The toboggan policy: exactly one of the (1-based) positions `range_start`
+/// and `range_end` holds the character. A position out of range fails.
+pub fn check_policy2(record: &PasswordRecord, unit: Unit) -> bool {
+    match unit.holds_at(&record.password, record.charachter, record.range_start, record.range_end) {
+        (Some(first), Some(second)) => first ^ second,
+        _ => false,
+    }
+}
+
+/// Why a record fails a policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// The built-in policy that failed, or the negated policy that held.
+    pub policy: String,
+    /// A short name for the kind of failure, used to group violations.
+    pub reason: &'static str,
+    pub message: String,
+}
+
+/// A rule a password record must satisfy.
+pub trait PasswordPolicy {
+    /// The name of the policy, in the syntax of [`parse_policy`].
+    fn name(&self) -> String;
+
+    /// Why `record` fails the policy; empty if it holds.
+    fn violations(&self, record: &PasswordRecord) -> Vec<Violation>;
+
+    fn check(&self, record: &PasswordRecord) -> bool {
+        self.violations(record).is_empty()
+    }
+}
+
+/// The sled rental policy, see [`check_policy1`].
+pub struct SledRental(pub Unit);
+impl PasswordPolicy for SledRental {
+    fn name(&self) -> String { String::from("sled") }
+
+    fn violations(&self, record: &PasswordRecord) -> Vec<Violation> {
+        let count = self.0.count(&record.password, record.charachter);
+        let reason = if count < record.range_start {
+            "too-few"
+        } else if count > record.range_end {
+            "too-many"
+        } else {
+            return Vec::new();
+        };
+        vec![Violation {
+            policy: self.name(),
+            reason,
+            message: format!("'{}' occurs {} times, allowed {}-{}",
+                             record.charachter, count, record.range_start, record.range_end),
+        }]
+    }
+
+    fn check(&self, record: &PasswordRecord) -> bool { check_policy1(record, self.0) }
+}
+
+/// The toboggan policy, see [`check_policy2`].
+pub struct Toboggan(pub Unit);
+impl PasswordPolicy for Toboggan {
+    fn name(&self) -> String { String::from("toboggan") }
+
+    fn violations(&self, record: &PasswordRecord) -> Vec<Violation> {
+        let (first, second) = (record.range_start, record.range_end);
+        let held = self.0.holds_at(&record.password, record.charachter, first, second);
+        let (reason, message) = match held {
+            (None, _) | (_, None) => {
+                let position = if held.0.is_none() { first } else { second };
+                ("out-of-range", format!("position {} is out of range for {} {}",
+                                         position, self.0.split(&record.password).count(), self.0.name()))
+            },
+            (Some(true), Some(true)) => ("both-positions",
+                             format!("both positions {} and {} contain '{}'", first, second, record.charachter)),
+            (Some(false), Some(false)) => ("neither-position",
+                               format!("neither position {} nor {} contains '{}'", first, second, record.charachter)),
+            _ => return Vec::new(),
+        };
+        vec![Violation { policy: self.name(), reason, message }]
+    }
 
-    (char1 == record.charachter) ^ (char2 == record.charachter)
+    fn check(&self, record: &PasswordRecord) -> bool { check_policy2(record, self.0) }
+}
+
+/// Both policies hold.
+pub struct And(pub Box<dyn PasswordPolicy>, pub Box<dyn PasswordPolicy>);
+impl PasswordPolicy for And {
+    fn name(&self) -> String { format!("({} and {})", self.0.name(), self.1.name()) }
+
+    fn violations(&self, record: &PasswordRecord) -> Vec<Violation> {
+        let mut violations = self.0.violations(record);
+        violations.extend(self.1.violations(record));
+        violations
+    }
+
+    fn check(&self, record: &PasswordRecord) -> bool { self.0.check(record) && self.1.check(record) }
+}
+
+/// Either policy holds.
+pub struct Or(pub Box<dyn PasswordPolicy>, pub Box<dyn PasswordPolicy>);
+impl PasswordPolicy for Or {
+    fn name(&self) -> String { format!("({} or {})", self.0.name(), self.1.name()) }
+
+    fn violations(&self, record: &PasswordRecord) -> Vec<Violation> {
+        let first = self.0.violations(record);
+        if first.is_empty() {
+            return first;
+        }
+        let second = self.1.violations(record);
+        if second.is_empty() {
+            return second;
+        }
+        first.into_iter().chain(second).collect()
+    }
+
+    fn check(&self, record: &PasswordRecord) -> bool { self.0.check(record) || self.1.check(record) }
+}
+
+/// The policy does not hold.
+pub struct Not(pub Box<dyn PasswordPolicy>);
+impl PasswordPolicy for Not {
+    fn name(&self) -> String { format!("not {}", self.0.name()) }
+
+    fn violations(&self, record: &PasswordRecord) -> Vec<Violation> {
+        if self.0.check(record) {
+            vec![Violation {
+                policy: self.name(),
+                reason: "negation-holds",
+                message: format!("satisfies {}", self.0.name()),
+            }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn check(&self, record: &PasswordRecord) -> bool { !self.0.check(record) }
+}
+
+/// The built-in policies by name. `part1` and `part2` are aliases for the
+/// policies of the two puzzle parts.
+pub fn builtin_policy(name: &str, unit: Unit) -> Option<Box<dyn PasswordPolicy>> {
+    match name {
+        "sled" | "part1" => Some(Box::new(SledRental(unit))),
+        "toboggan" | "part2" => Some(Box::new(Toboggan(unit))),
+        _ => None,
+    }
+}
+
+/// Parse a policy expression like `sled and not (toboggan or part1)`.
+/// `not` binds tighter than `and`, which binds tighter than `or`. The
+/// built-in policies count and index in `unit`.
+pub fn parse_policy(expr: &str, unit: Unit) -> Result<Box<dyn PasswordPolicy>> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+    for (i, c) in expr.char_indices() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if let Some(start) = word_start.take() {
+                tokens.push(&expr[start..i]);
+            }
+            if !c.is_whitespace() {
+                tokens.push(&expr[i..i + 1]);
+            }
+        } else if word_start.is_none() {
+            word_start = Some(i);
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(&expr[start..]);
+    }
+
+    let mut parser = PolicyParser { expr, tokens, next: 0, unit };
+    let policy = parser.or()?;
+    match parser.tokens.get(parser.next) {
+        Some(token) => Err(parser.error(token, "expected 'and', 'or' or the end of the policy")),
+        None => Ok(policy),
+    }
+}
+
+struct PolicyParser<'a> {
+    expr: &'a str,
+    tokens: Vec<&'a str>,
+    next: usize,
+    unit: Unit,
+}
+
+impl<'a> PolicyParser<'a> {
+    fn error(&self, token: &str, message: &str) -> Error {
+        Diagnostic::spanning(1, self.expr, token, message).into()
+    }
+
+    fn eat(&mut self, word: &str) -> bool {
+        let found = self.tokens.get(self.next) == Some(&word);
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Box<dyn PasswordPolicy>> {
+        let mut policy = self.and()?;
+        while self.eat("or") {
+            policy = Box::new(Or(policy, self.and()?));
+        }
+        Ok(policy)
+    }
+
+    fn and(&mut self) -> Result<Box<dyn PasswordPolicy>> {
+        let mut policy = self.unary()?;
+        while self.eat("and") {
+            policy = Box::new(And(policy, self.unary()?));
+        }
+        Ok(policy)
+    }
+
+    fn unary(&mut self) -> Result<Box<dyn PasswordPolicy>> {
+        let token = match self.tokens.get(self.next) {
+            Some(&t) => t,
+            None => return Err(self.error(&self.expr[self.expr.len()..], "expected a policy")),
+        };
+        self.next += 1;
+        match token {
+            "not" => Ok(Box::new(Not(self.unary()?))),
+            "(" => {
+                let policy = self.or()?;
+                if self.eat(")") {
+                    Ok(policy)
+                } else {
+                    Err(self.error(token, "unclosed '('"))
+                }
+            },
+            name => builtin_policy(name, self.unit)
+                .ok_or_else(|| self.error(name, &format!("unknown policy '{}', expected sled or toboggan", name))),
+        }
+    }
+}
+
+/// Count the records satisfying `policy`.
+pub fn count_valid(records: &[PasswordRecord], policy: &dyn PasswordPolicy) -> usize {
+    records.iter().filter(|r| policy.check(r)).count()
+}
+
+/// A record failing a policy, with the reasons.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordViolations {
+    pub line: usize,
+    pub password: String,
+    pub violations: Vec<Violation>,
+}
+
+/// The header of the CSV rows of [`RecordViolations::csv_rows`].
+pub const CSV_HEADER: &str = "line,password,policy,reason,message\n";
+
+impl RecordViolations {
+    /// One CSV row per violation.
+    pub fn csv_rows(&self) -> String {
+        let mut out = String::new();
+        for v in &self.violations {
+            writeln!(out, "{},{},{},{},{}", self.line, csv_string(&self.password),
+                     csv_string(&v.policy), v.reason, csv_string(&v.message)).unwrap();
+        }
+        out
+    }
+}
+
+/// The result of checking records against a policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audit {
+    pub policy: String,
+    pub total: usize,
+    pub failed: usize,
+    /// The number of violations per policy and reason.
+    pub histogram: BTreeMap<(String, &'static str), usize>,
+    /// The failing records, if kept, see [`Audit::tally`].
+    pub failures: Vec<RecordViolations>,
+}
+
+impl Audit {
+    pub fn new(policy: &dyn PasswordPolicy) -> Audit {
+        Audit {
+            policy: policy.name(),
+            total: 0,
+            failed: 0,
+            histogram: BTreeMap::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// Check `record` against `policy` and count it. A failing record is
+    /// returned rather than kept, so a stream of any length can be tallied.
+    pub fn tally(&mut self, record: &PasswordRecord, policy: &dyn PasswordPolicy) -> Option<RecordViolations> {
+        self.total += 1;
+        if policy.check(record) {
+            return None;
+        }
+        self.failed += 1;
+        let violations = policy.violations(record);
+        for v in &violations {
+            *self.histogram.entry((v.policy.clone(), v.reason)).or_insert(0) += 1;
+        }
+        Some(RecordViolations { line: record.line, password: record.password.clone(), violations })
+    }
+
+    pub fn valid(&self) -> usize {
+        self.total - self.failed
+    }
+
+    /// Render the audit in `format`. Text gives the totals and a histogram
+    /// of the reasons, CSV one row per violation, and JSON both.
+    pub fn render(&self, format: Format) -> String {
+        let mut out = String::new();
+        match format {
+            Format::Text => {
+                writeln!(out, "{} of {} passwords satisfy {}", self.valid(), self.total, self.policy).unwrap();
+                let widest = self.histogram.values().copied().max().unwrap_or(0);
+                if widest > 0 {
+                    writeln!(out, "\nFailure reasons:").unwrap();
+                }
+                for ((policy, reason), count) in &self.histogram {
+                    let bar = "#".repeat((count * 40).div_ceil(widest));
+                    writeln!(out, "  {:<30} {:>8} {}", format!("{}: {}", policy, reason), count, bar).unwrap();
+                }
+            },
+            Format::Csv => {
+                out.push_str(CSV_HEADER);
+                for f in &self.failures {
+                    out.push_str(&f.csv_rows());
+                }
+            },
+            Format::Json => {
+                let violations: Vec<String> = self.failures.iter()
+                    .flat_map(|f| f.violations.iter().map(move |v| (f, v)))
+                    .map(|(f, v)| format!("    {{\"line\": {}, \"password\": {}, \"policy\": {}, \
+                                           \"reason\": \"{}\", \"message\": {}}}",
+                                          f.line, json_string(&f.password), json_string(&v.policy),
+                                          v.reason, json_string(&v.message)))
+                    .collect();
+                let histogram: Vec<String> = self.histogram.iter()
+                    .map(|((policy, reason), count)| format!("    {{\"policy\": {}, \"reason\": \"{}\", \"count\": {}}}",
+                                                             json_string(policy), reason, count))
+                    .collect();
+                writeln!(out, "{{\n  \"policy\": {},\n  \"total\": {},\n  \"valid\": {},",
+                         json_string(&self.policy), self.total, self.valid()).unwrap();
+                writeln!(out, "  \"violations\": [\n{}\n  ],", violations.join(",\n")).unwrap();
+                writeln!(out, "  \"histogram\": [\n{}\n  ]\n}}", histogram.join(",\n")).unwrap();
+            },
+        }
+        out
+    }
+}
+
+/// Check every record against `policy`, keeping the failing records. Stops
+/// at the first record that could not be read.
+pub fn audit<I>(records: I, policy: &dyn PasswordPolicy) -> Result<Audit>
+    where I: IntoIterator<Item = Result<PasswordRecord>>
+{
+    let mut audit = Audit::new(policy);
+    for record in records {
+        if let Some(failure) = audit.tally(&record?, policy) {
+            audit.failures.push(failure);
+        }
+    }
+    Ok(audit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc";
+
+    fn count(expr: &str) -> usize {
+        count_valid(&parse(EXAMPLE).unwrap(), &*parse_policy(expr, Unit::Char).unwrap())
+    }
+
+    #[test]
+    fn policies() {
+        assert_eq!(count("sled"), 2);
+        assert_eq!(count("toboggan"), 1);
+        assert_eq!(count("part1 and part2"), 1);
+        assert_eq!(count("sled and not toboggan"), 1);
+        assert_eq!(count("not sled or toboggan"), 2);
+        assert_eq!(count("not (sled or toboggan)"), 1);
+        assert_eq!(parse_policy("not (sled or toboggan) and sled", Unit::Char).unwrap().name(),
+                   "(not (sled or toboggan) and sled)");
+    }
+
+    #[test]
+    fn violations() {
+        let audit = audit_example("sled and not toboggan");
+        assert_eq!(audit.valid(), 1);
+        let lines: Vec<usize> = audit.failures.iter().map(|f| f.line).collect();
+        assert_eq!(lines, vec![1, 2]);
+        assert_eq!(audit.failures[0].violations[0].message, "satisfies toboggan");
+        assert_eq!(audit.failures[1].violations, vec![Violation {
+            policy: String::from("sled"),
+            reason: "too-few",
+            message: String::from("'b' occurs 0 times, allowed 1-3"),
+        }]);
+
+        let audit = audit_example("toboggan");
+        assert_eq!(audit.failures[1].violations[0].message, "both positions 2 and 9 contain 'c'");
+        assert_eq!(audit.histogram.into_iter().collect::<Vec<_>>(),
+                   vec![((String::from("toboggan"), "both-positions"), 1),
+                        ((String::from("toboggan"), "neither-position"), 1)]);
+    }
+
+    fn audit_example(expr: &str) -> Audit {
+        audit(records(EXAMPLE.as_bytes()), &*parse_policy(expr, Unit::Char).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn audit_formats() {
+        let audit = audit_example("sled or toboggan");
+        assert_eq!(audit.render(Format::Csv),
+                   "line,password,policy,reason,message\n\
+                    2,cdefg,sled,too-few,\"'b' occurs 0 times, allowed 1-3\"\n\
+                    2,cdefg,toboggan,neither-position,neither position 1 nor 3 contains 'b'\n");
+        let json = audit.render(Format::Json);
+        assert!(json.contains("\"total\": 3,\n  \"valid\": 2,"));
+        assert!(json.contains("{\"line\": 2, \"password\": \"cdefg\", \"policy\": \"sled\", \"reason\": \"too-few\""));
+        assert!(audit.render(Format::Text).starts_with("2 of 3 passwords satisfy (sled or toboggan)\n\nFailure reasons:\n"));
+    }
+
+    #[test]
+    fn streaming() {
+        let input = "1-3 a: abcde\r\n1-3 b: cdefg\nnonsense\n2-9 c: ccccccccc\n";
+        let mut stream = records(input.as_bytes());
+        assert_eq!(stream.next().unwrap().unwrap().password, "abcde");
+        assert_eq!(stream.next().unwrap().unwrap().line, 2);
+        match stream.next() {
+            Some(Err(Error::Parse(d))) => assert_eq!(d.line, 3),
+            r => panic!("expected a parse error, got {:?}", r),
+        }
+        assert!(stream.next().is_none());
+
+        let mut stream = records(&b"1-3 a: abc\n1-3 a: ab\xffc\n"[..]);
+        assert!(stream.next().unwrap().is_ok());
+        match stream.next() {
+            Some(Err(Error::Parse(d))) => {
+                assert_eq!((d.line, d.column(), d.source_line.as_str()), (2, 10, "1-3 a: ab\u{fffd}c"));
+                assert!(d.message.ends_with("invalid UTF-8"));
+            },
+            r => panic!("expected a parse error, got {:?}", r),
+        }
+        assert_eq!(parse("").unwrap().len(), 0);
+    }
+
+    fn record(line: &str) -> PasswordRecord {
+        parse_record(1, line).unwrap()
+    }
+
+    #[test]
+    fn units() {
+        let unit: Unit = "grapheme".parse().unwrap();
+        assert_eq!(unit, Unit::Grapheme);
+        assert!("codepoint".parse::<Unit>().is_err());
+
+        assert_eq!(Unit::Char.holds_at("abcde", 'd', 4, 2), (Some(true), Some(false)));
+        assert_eq!(Unit::Char.holds_at("abcde", 'c', 3, 3), (Some(true), Some(true)));
+        assert_eq!(Unit::Char.holds_at("abcde", 'a', 1, 6), (Some(true), None));
+        assert_eq!(Unit::Char.holds_at("abcde", 'a', 0, 1), (None, Some(true)));
+
+        // 'é' as one code point, then as 'e' and a combining accent.
+        let password = "\u{e9}e\u{301}xe";
+        assert_eq!([Unit::Byte, Unit::Char, Unit::Grapheme].map(|u| u.count(password, 'e')), [2, 2, 1]);
+        assert_eq!([Unit::Byte, Unit::Char, Unit::Grapheme].map(|u| u.count(password, '\u{e9}')), [0, 1, 1]);
+        assert_eq!(Unit::Byte.holds_at(password, 'x', 6, 4), (Some(true), Some(false)));
+        assert_eq!(Unit::Char.holds_at(password, 'x', 4, 2), (Some(true), Some(false)));
+        // A Devanagari conjunct (GB9c) and a flag are single clusters.
+        assert_eq!(Unit::Grapheme.split("\u{915}\u{94D}\u{937}\u{93F}\u{1F1F8}\u{1F1EA}").count(), 2);
+    }
+
+    #[test]
+    fn out_of_range_positions_fail() {
+        let r = record("1-9 a: abc");
+        assert!(!check_policy2(&r, Unit::Char));
+        assert_eq!(Toboggan(Unit::Char).violations(&r)[0].message, "position 9 is out of range for 3 chars");
+        let r = record("0-2 b: abc");
+        assert!(!check_policy2(&r, Unit::Byte));
+        assert_eq!(Toboggan(Unit::Byte).violations(&r)[0].reason, "out-of-range");
+
+        let r = record("1-2 x: e\u{301}x");
+        assert!(!check_policy2(&r, Unit::Char));
+        assert!(check_policy2(&r, Unit::Grapheme));
+        let r = record("2-3 x: e\u{301}x");
+        assert!(check_policy2(&r, Unit::Char));
+        assert_eq!(Toboggan(Unit::Grapheme).violations(&r)[0].message, "position 3 is out of range for 2 graphemes");
+    }
+
+    #[test]
+    fn policy_errors() {
+        let column = |expr| match parse_policy(expr, Unit::Char) {
+            Err(Error::Parse(d)) => d.column(),
+            _ => panic!("expected an error for '{}'", expr),
+        };
+        assert_eq!(column("sled and bobsled"), 10);
+        assert_eq!(column("sled toboggan"), 6);
+        assert_eq!(column("(sled or toboggan"), 1);
+        assert_eq!(column("not"), 4);
+    }
+
+    #[test]
+    fn record_errors() {
+        match parse_record(1, "1-3 ab: abc") {
+            Err(Error::Parse(d)) => {
+                assert_eq!(d.columns, 6..7);
+                assert!(d.message.ends_with("expected a single policy character"));
+            },
+            r => panic!("expected a parse error, got {:?}", r),
+        }
+        assert_eq!(record("1-3 \u{e9}: \u{e9}").charachter, '\u{e9}');
+    }
 }
@@ -1,117 +1,288 @@
-use std::fs::File;
-use std::path::Path;
-use std::io::{BufRead, BufReader, Seek};
-use std::io;
-use std::error::Error;
-
-pub fn run(input_path: &Path) -> bool {
-    let map = match parse(input_path) {
-        Ok(v) => v,
-        Err(e) => {
-            eprintln!("There was a problem parsing the input file:");
-            eprintln!("{}", e);
-            return false;
-        }
-    };
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use super::common::Grid;
+use super::error::{Error, Result};
+use super::solution::{Answer, Solution};
 
-    let num_trees_3_1 = count_trees_along_direction(&map, 3,1);
-    println!("[Part 1] Num trees along direction (3, 1): {}", num_trees_3_1);
+/// Day 3: Toboggan Trajectory.
+pub struct Day03;
+impl Solution for Day03 {
+    type Input = TobogganMap;
 
-    // Part 2
-    let num_trees_1_1 = count_trees_along_direction(&map, 1,1);
-    let num_trees_5_1 = count_trees_along_direction(&map, 5,1);
-    let num_trees_7_1 = count_trees_along_direction(&map, 7,1);
-    let num_trees_1_2 = count_trees_along_direction(&map, 1,2);
-    
-    let product = 
-        num_trees_1_1 *
-        num_trees_3_1 *
-        num_trees_5_1 *
-        num_trees_7_1 *
-        num_trees_1_2;
-    println!("[Part 2] Product of num. trees along slopes: : {}", product);
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(map: &Self::Input) -> Result<Answer> {
+        Ok(count_trees_along_direction(map, 3, 1)?.into())
+    }
 
-    true
+    fn part2(map: &Self::Input) -> Result<Answer> {
+        Ok(product_of_trees(map, &PUZZLE_SLOPES)?.into())
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-enum TileType {
+pub enum TileType {
     Open,
     Tree
 }
+impl fmt::Display for TileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileType::Open => write!(f, "."),
+            TileType::Tree => write!(f, "#"),
+        }
+    }
+}
 
+/// The map of open squares and trees. It repeats infinitely to the right.
 #[derive(Debug)]
-struct TobogganMap {
-    rows: usize,
-    cols: usize,
-    tiles: Vec<TileType>
+pub struct TobogganMap {
+    pub grid: Grid<TileType>
 }
 impl TobogganMap {
-    fn get_tile(&self, x: usize, y: usize) -> TileType {
-        let _x = x % self.cols;
-        let _y = y;
-
-        self.tiles[ self.cols * _y + _x]
-    }
-}
-
-fn parse(filepath: &Path) -> Result<TobogganMap, Box<dyn Error>> {
-    let mut file = File::open(filepath).expect("Could not open file");
-    let num_lines = BufReader::new(&file).lines().count();
-    file.seek(io::SeekFrom::Start(0))?;
-
-    let rows = num_lines;
-    let cols;
-    let mut tiles: Vec<TileType>;
-    {
-        
-        let line = BufReader::new(&file).lines().next().unwrap()?;
-        cols = line.chars().count();
-        tiles = vec![TileType::Open; rows*cols];
-        file.seek(io::SeekFrom::Start(0))?;
-    }
-
-    let reader = BufReader::new(&file);
-    for (i_line, line) in reader.lines().enumerate() {
-        for (i_c, c) in line?.chars().enumerate() {
-            let tile_type = match c {
-                '.' => Ok(TileType::Open),
-                '#' => Ok(TileType::Tree),
-                _ => Err(
-                    Box::new(
-                        io::Error::new( 
-                            io::ErrorKind::Other, 
-                            format!("Could not parse row {} col {}: unexpected charachter {}", i_line, i_c, c)
-                        )
-                    )
-                )
-            }?;
-
-            tiles[i_line * cols + i_c] = tile_type;
+    pub fn rows(&self) -> usize {
+        self.grid.rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.grid.cols()
+    }
+
+    /// The tile at column `x` and row `y`, wrapping `x` around the map width.
+    /// Rows below the bottom of the map are open.
+    pub fn get_tile(&self, x: usize, y: usize) -> TileType {
+        self.grid.get(x % self.cols(), y).copied().unwrap_or(TileType::Open)
+    }
+}
+impl fmt::Display for TobogganMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.grid.fmt(f)
+    }
+}
+
+/// Parse a map of `.` (open) and `#` (tree) characters.
+pub fn parse(input: &str) -> Result<TobogganMap> {
+    let grid = Grid::parse(input, |c| match c {
+        '.' => Some(TileType::Open),
+        '#' => Some(TileType::Tree),
+        _ => None
+    })?;
+    if grid.is_empty() {
+        return Err(Error::Validation(String::from("the map is empty")));
+    }
+
+    Ok(TobogganMap { grid })
+}
+
+/// The rational slope of the toboggan: `dx` columns to the right (left if
+/// negative) for every `dy` rows down. Written `dx/dy`, e.g. `3/1` or `-1/2`.
+///
+/// Slopes are kept in lowest terms, so `2/4` is the same slope as `1/2`: the
+/// toboggan checks every square with whole coordinates on its line. `dy` is
+/// always at least 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+    dx: i64,
+    dy: usize,
+}
+
+/// The slopes multiplied in part 2.
+pub const PUZZLE_SLOPES: [Slope; 5] = [
+    Slope { dx: 1, dy: 1 },
+    Slope { dx: 3, dy: 1 },
+    Slope { dx: 5, dy: 1 },
+    Slope { dx: 7, dy: 1 },
+    Slope { dx: 1, dy: 2 },
+];
+
+impl Slope {
+    /// The slope moving `dx` right for every `dy` down, reduced to lowest
+    /// terms. `dy` must not be zero.
+    pub fn new(dx: i64, dy: usize) -> Result<Slope> {
+        if dy == 0 {
+            return Err(Error::Validation(format!(
+                "slope {}/{} never reaches the bottom, it needs to move down", dx, dy)));
         }
+        let divisor = gcd(dx.unsigned_abs(), dy as u64);
+        Ok(Slope {
+            dx: (i128::from(dx) / i128::from(divisor)) as i64,
+            dy: (dy as u64 / divisor) as usize,
+        })
     }
 
-    Ok(TobogganMap {
-        rows,
-        cols,
-        tiles
-    })
+    pub fn dx(self) -> i64 {
+        self.dx
+    }
 
+    pub fn dy(self) -> usize {
+        self.dy
+    }
 }
 
-fn count_trees_along_direction(map: &TobogganMap, dx: usize, dy: usize) -> usize { 
-    let mut x: usize = 0;
-    let mut y: usize = 0;
-    let mut num_trees = 0;
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+impl FromStr for Slope {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Slope, String> {
+        let (dx, dy) = s.split_once('/')
+            .ok_or_else(|| format!("expected a slope like 3/1, found '{}'", s))?;
+        let dx = dx.parse().map_err(|e| format!("slope '{}': {}", s, e))?;
+        let dy = dy.parse().map_err(|e| format!("slope '{}': {}", s, e))?;
+        Slope::new(dx, dy).map_err(|e| e.to_string())
+    }
+}
 
-    while y + dy <= map.rows {
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.dx, self.dy)
+    }
+}
+
+/// Count the trees hit going from the top-left corner to the bottom of the
+/// map along `slope`. The map wraps around in both directions.
+pub fn count_trees(map: &TobogganMap, slope: Slope) -> usize {
+    let cols = map.cols() as i64;
+    let step = slope.dx.rem_euclid(cols) as usize;
+    let mut x = 0;
+    let mut num_trees = 0;
+    for y in (0..map.rows()).step_by(slope.dy) {
         if map.get_tile(x, y) == TileType::Tree {
             num_trees += 1;
         }
+        x = (x + step) % map.cols();
+    }
+    num_trees
+}
+
+/// Count the trees hit moving `dx` right and `dy` down each step, see
+/// [`count_trees`].
+pub fn count_trees_along_direction(map: &TobogganMap, dx: usize, dy: usize) -> Result<usize> {
+    let dx = i64::try_from(dx)
+        .map_err(|_| Error::Validation(format!("slope {}/{} moves too far right", dx, dy)))?;
+    Ok(count_trees(map, Slope::new(dx, dy)?))
+}
 
-        x += dx;
-        y += dy;
+/// The product of the trees hit along every slope.
+pub fn product_of_trees(map: &TobogganMap, slopes: &[Slope]) -> Result<u64> {
+    slopes.iter().try_fold(1u64, |product, &slope| {
+        product.checked_mul(count_trees(map, slope) as u64)
+            .ok_or_else(|| Error::Overflow(format!("product of trees along {} slopes", slopes.len())))
+    })
+}
+
+/// The slopes sharing the fewest or most trees found by [`search_slopes`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extreme {
+    pub trees: usize,
+    /// In the order searched: by `dy`, then `dx`.
+    pub slopes: Vec<Slope>,
+}
+
+impl Extreme {
+    fn consider(&mut self, slope: Slope, trees: usize, better: bool) {
+        if better || self.slopes.is_empty() {
+            self.trees = trees;
+            self.slopes.clear();
+        }
+        if trees == self.trees {
+            self.slopes.push(slope);
+        }
     }
+}
 
-    num_trees
+/// The result of [`search_slopes`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlopeSearch {
+    pub fewest: Extreme,
+    pub most: Extreme,
+}
+
+/// Try every slope `dx/dy` in lowest terms with `|dx| <= bound` and
+/// `1 <= dy <= bound`. Slopes whose `dx` differ by a multiple of the map width
+/// hit the same trees.
+///
+/// Every `dy` checks about `2 * bound * rows / dy` squares, so the search
+/// costs about `2 * bound * rows * ln(bound)`.
+pub fn search_slopes(map: &TobogganMap, bound: usize) -> Result<SlopeSearch> {
+    if bound == 0 {
+        return Err(Error::Validation(String::from("the search bound must be at least 1")));
+    }
+    let bound_dx = i64::try_from(bound)
+        .map_err(|_| Error::Validation(format!("search bound {} is too large", bound)))?;
+    let mut search = SlopeSearch {
+        fewest: Extreme { trees: 0, slopes: Vec::new() },
+        most: Extreme { trees: 0, slopes: Vec::new() },
+    };
+    for dy in 1..=bound {
+        for dx in -bound_dx..=bound_dx {
+            if gcd(dx.unsigned_abs(), dy as u64) != 1 {
+                continue;
+            }
+            let slope = Slope { dx, dy };
+            let trees = count_trees(map, slope);
+            search.fewest.consider(slope, trees, trees < search.fewest.trees);
+            search.most.consider(slope, trees, trees > search.most.trees);
+        }
+    }
+    Ok(search)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../examples/day03/example.txt");
+
+    fn slope(s: &str) -> Slope {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn slopes() {
+        let map = parse(EXAMPLE).unwrap();
+        let counts: Vec<usize> = PUZZLE_SLOPES.iter().map(|&s| count_trees(&map, s)).collect();
+        assert_eq!(counts, vec![2, 7, 3, 4, 2]);
+        assert_eq!(product_of_trees(&map, &PUZZLE_SLOPES).unwrap(), 336);
+
+        // Moving left wraps around to the right edge.
+        assert_eq!(count_trees(&map, slope("-8/1")), count_trees(&map, slope("3/1")));
+        assert_eq!(count_trees(&map, slope("0/1")), 3);
+        // Slopes are rationals in lowest terms.
+        assert_eq!(slope("2/4"), slope("1/2"));
+        assert_eq!(slope("-6/3").to_string(), "-2/1");
+        assert_eq!(slope("0/5").to_string(), "0/1");
+        assert_eq!(Slope::new(i64::MIN, 1 << 63).unwrap().to_string(), "-1/1");
+        assert_eq!(slope("-1/2"), Slope::new(-1, 2).unwrap());
+        assert_eq!((slope("-1/2").dx(), slope("-1/2").dy()), (-1, 2));
+        assert_eq!(slope("-1/2").to_string(), "-1/2");
+        assert!(matches!(Slope::new(3, 0), Err(Error::Validation(_))));
+        assert!(matches!(count_trees_along_direction(&map, 3, 0), Err(Error::Validation(_))));
+        assert!("3/0".parse::<Slope>().is_err());
+        assert!("3/-1".parse::<Slope>().is_err());
+        assert!("3".parse::<Slope>().is_err());
+    }
+
+    #[test]
+    fn search() {
+        let map = parse(EXAMPLE).unwrap();
+        let search = search_slopes(&map, 2).unwrap();
+        assert_eq!(search.fewest, Extreme { trees: 1, slopes: vec![slope("2/1")] });
+        assert_eq!(search.most, Extreme { trees: 5, slopes: vec![slope("-1/1")] });
+
+        let search = search_slopes(&map, 3).unwrap();
+        assert_eq!(search.fewest, Extreme { trees: 0, slopes: vec![slope("1/3")] });
+        assert_eq!(search.most, Extreme { trees: 7, slopes: vec![slope("3/1")] });
+        assert!(search_slopes(&map, 0).is_err());
+    }
 }
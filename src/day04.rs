@@ -1,48 +1,49 @@
-use std::fs::File;
-use std::path::Path;
-use std::io::{BufRead, BufReader};
-use std::error::Error;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+use super::diagnostic::Diagnostic;
+use super::error::{Error, Result};
+use super::solution::{Answer, Solution};
 
-pub fn run(input_path: &Path) -> bool {
-    let file = File::open(input_path).expect("Could not open file");
-    let reader = BufReader::new(&file);
-    let passports = match parse(reader) {
-        Ok(v) => v,
-        Err(e) => {
-            eprintln!("There was a problem parsing the input file:");
-            eprintln!("{}", e);
-            return false
-        }
-    };
+/// Day 4: Passport Processing.
+pub struct Day04;
+impl Solution for Day04 {
+    type Input = Vec<Passport>;
 
-    println!("[Part 1] Num valid passports: {}",
-             passports.iter().filter(|p| p.is_valid_part1()).count());
-    
-    println!("[Part 2] Num valid passports: {}", 
-             passports.iter().filter(|p| p.is_valid_part2()).count());
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input.as_bytes())
+    }
 
-    true
+    fn part1(passports: &Self::Input) -> Result<Answer> {
+        Ok(passports.iter().filter(|p| p.is_valid_part1()).count().into())
+    }
+
+    fn part2(passports: &Self::Input) -> Result<Answer> {
+        Ok(passports.iter().filter(|p| p.is_valid_part2()).count().into())
+    }
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
-enum PassportHeight {
+pub enum PassportHeight {
     Centimeters(i32),
     Inches(i32),
 }
 
+/// A passport with every field optional, as found in the batch file.
 #[derive(Debug, Clone)]
-struct Passport {
-    byr: Option<usize>,
-    iyr: Option<usize>,
-    eyr: Option<usize>,
-    hgt: Option<PassportHeight>,
-    hcl: Option<String>,
-    ecl: Option<String>,
-    pid: Option<String>,
-    cid: Option<String>,
+pub struct Passport {
+    pub byr: Option<usize>,
+    pub iyr: Option<usize>,
+    pub eyr: Option<usize>,
+    pub hgt: Option<PassportHeight>,
+    pub hcl: Option<String>,
+    pub ecl: Option<String>,
+    pub pid: Option<String>,
+    pub cid: Option<String>,
 }
 impl Passport {
-    fn is_valid_part1(&self) -> bool {
+    /// All required fields are present. `cid` is optional.
+    pub fn is_valid_part1(&self) -> bool {
         // Make cid not required
         self.byr.is_some() &&
         self.iyr.is_some() &&
@@ -54,27 +55,28 @@ impl Passport {
         //self.cid.is_some()
     }
     
-    fn is_valid_part2(&self) -> bool {
-        let valid_byr = self.byr.map_or(false, |v| (1920..=2002).contains(&v));
-        let valid_iyr = self.iyr.map_or(false, |v| (2010..=2020).contains(&v));
-        let valid_eyr = self.eyr.map_or(false, |v| (2020..=2030).contains(&v));
-        let valid_hgt = self.hgt.map_or(false, |v| {
+    /// All required fields are present and hold valid values.
+    pub fn is_valid_part2(&self) -> bool {
+        let valid_byr = self.byr.is_some_and(|v| (1920..=2002).contains(&v));
+        let valid_iyr = self.iyr.is_some_and(|v| (2010..=2020).contains(&v));
+        let valid_eyr = self.eyr.is_some_and(|v| (2020..=2030).contains(&v));
+        let valid_hgt = self.hgt.is_some_and(|v| {
             match v {
                 PassportHeight::Centimeters(h) => (150..=193).contains(&h),
                 PassportHeight::Inches(h) => (59..=76).contains(&h),
             }
         });
-        let valid_hcl = self.hcl.as_ref().map_or(false, |v| {
+        let valid_hcl = self.hcl.as_ref().is_some_and(|v| {
             (v.starts_with('#')) &&
             (v.chars().count() == 7) &&
-            (v.as_str()[1..].chars().all(|c| matches!(c, '0'..='9') || matches!(c, 'a'..='f')))
+            (v.as_str()[1..].chars().all(|c| c.is_ascii_digit() || matches!(c, 'a'..='f')))
         });
-        let valid_ecl = self.ecl.as_ref().map_or(false, |v| {
+        let valid_ecl = self.ecl.as_ref().is_some_and(|v| {
             ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].iter().any(|color| color == &v.as_str())
         });
-        let valid_pid = self.pid.as_ref().map_or(false, |v| {
+        let valid_pid = self.pid.as_ref().is_some_and(|v| {
             v.chars().count() == 9 &&
-            v.chars().all(|c| matches!(c, '0'..='9'))
+            v.chars().all(|c| c.is_ascii_digit())
         });
 
         valid_byr &&
@@ -87,9 +89,17 @@ impl Passport {
     }
 }
 
-fn parse<R: BufRead>(reader: R) -> Result<Vec<Passport>, Box<dyn Error>> {
+/// Parse `value`, a subslice of `line`, as the value of field `key`.
+fn parse_field<T: FromStr>(key: &str, value: &str, line_no: usize, line: &str) -> Result<T>
+where T::Err: fmt::Display {
+    value.parse::<T>()
+        .map_err(|e| Diagnostic::spanning(line_no, line, value, format!("{}: {}", key, e)).into())
+}
+
+/// Parse a batch file of passports separated by blank lines.
+pub fn parse<R: BufRead>(reader: R) -> Result<Vec<Passport>> {
     let mut passports: Vec<Passport> = Vec::new();
-    let mut lines = reader.lines();
+    let mut lines = reader.lines().enumerate();
 
     let mut reached_eof = false;
     while !reached_eof {
@@ -108,9 +118,9 @@ fn parse<R: BufRead>(reader: R) -> Result<Vec<Passport>, Box<dyn Error>> {
         // indicating a new passport
         loop {
             let current_line = lines.next();
-            let line_string = match current_line {
-                Some(Ok(s)) => s,
-                Some(Err(e)) => panic!(e),
+            let (i_line, line_string) = match current_line {
+                Some((i, Ok(s))) => (i + 1, s),
+                Some((_, Err(e))) => return Err(Error::Io(e)),
                 None => {
                     // We have reached the end of the file.
                     reached_eof = true;
@@ -124,28 +134,29 @@ fn parse<R: BufRead>(reader: R) -> Result<Vec<Passport>, Box<dyn Error>> {
                 break;
             }
 
+            let line = line_string.as_str();
             for pair in pairs {
-                let mut key_value_iter = pair.split(':');
-                let key = key_value_iter.next().expect("Expected key");
-                let value = key_value_iter.next().expect("Expected value");
+                let err = |message: String| Error::from(Diagnostic::spanning(i_line, line, pair, message));
+                let (key, value) = pair.split_once(':')
+                    .ok_or_else(|| err(format!("expected 'key:value', found '{}'", pair)))?;
                 match key {
-                    "byr" => passport.byr = Some(value.parse::<usize>()?),
-                    "iyr" => passport.iyr = Some(value.parse::<usize>()?),
-                    "eyr" => passport.eyr = Some(value.parse::<usize>()?),
+                    "byr" => passport.byr = Some(parse_field(key, value, i_line, line)?),
+                    "iyr" => passport.iyr = Some(parse_field(key, value, i_line, line)?),
+                    "eyr" => passport.eyr = Some(parse_field(key, value, i_line, line)?),
                     "hgt" => passport.hgt = {
-                        let unit = &value[value.len()-2..];
-                        let number = &value[..value.len()-2];
-                        match unit {
-                            "cm" => Some(PassportHeight::Centimeters(number.parse::<i32>()?)),
-                            "in" => Some(PassportHeight::Inches(number.parse::<i32>()?)),
-                            _ => None,
+                        if let Some(number) = value.strip_suffix("cm") {
+                            Some(PassportHeight::Centimeters(parse_field(key, number, i_line, line)?))
+                        } else if let Some(number) = value.strip_suffix("in") {
+                            Some(PassportHeight::Inches(parse_field(key, number, i_line, line)?))
+                        } else {
+                            None
                         }
                     },
                     "hcl" => passport.hcl = Some(String::from(value)),
                     "ecl" => passport.ecl = Some(String::from(value)),
                     "pid" => passport.pid = Some(String::from(value)),
                     "cid" => passport.cid = Some(String::from(value)),
-                    _ => panic!("Unexpected key {}", key)
+                    _ => return Err(err(format!("unexpected key {}", key)))
                 }
             }
         }
@@ -160,26 +171,13 @@ fn parse<R: BufRead>(reader: R) -> Result<Vec<Passport>, Box<dyn Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::BufReader;
 
     #[test]
     fn example_input_part1() {
-        let data = 
-r#"ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
-byr:1937 iyr:2017 cid:147 hgt:183cm
-
-iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
-hcl:#cfa07d byr:1929
-
-hcl:#ae17e1 iyr:2013
-eyr:2024
-ecl:brn pid:760753108 byr:1931
-hgt:179cm
-
-hcl:#cfa07d eyr:2025 pid:166559648
-iyr:2011 ecl:brn hgt:59in"#;
+        let data = include_str!("../examples/day04/example.txt");
 
         let passports = parse(BufReader::new(data.as_bytes())).unwrap();
-        println!("{:?}", passports[0]);
         
         assert_eq!(passports[0].byr, Some(1937));
         assert_eq!(passports[0].iyr, Some(2017));
@@ -190,10 +188,10 @@ iyr:2011 ecl:brn hgt:59in"#;
         assert_eq!(passports[0].pid, Some(String::from("860033327")));
         assert_eq!(passports[0].cid, Some(String::from("147")));
 
-        assert_eq!(passports[0].is_valid_part1(), true);
-        assert_eq!(passports[1].is_valid_part1(), false);
-        assert_eq!(passports[2].is_valid_part1(), true);
-        assert_eq!(passports[3].is_valid_part1(), false);
+        assert!(passports[0].is_valid_part1());
+        assert!(!passports[1].is_valid_part1());
+        assert!(passports[2].is_valid_part1());
+        assert!(!passports[3].is_valid_part1());
     }
     
     #[test]
@@ -208,53 +206,53 @@ iyr:2011 ecl:brn hgt:59in"#;
             pid: Some(String::from("000000001")),
             cid: None,
         };
-        assert_eq!(passport_valid.is_valid_part2(), true);
+        assert!(passport_valid.is_valid_part2());
         
         let mut passport_tmp;
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.byr = Some(2003);
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.hgt = None;
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.hgt = Some(PassportHeight::Centimeters(149));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.hgt = Some(PassportHeight::Centimeters(194));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.hgt = Some(PassportHeight::Inches(58));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.hgt = Some(PassportHeight::Inches(77));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.hcl = Some(String::from("!123abc"));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.hcl = Some(String::from("#123ab"));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
 
         passport_tmp = passport_valid.clone(); 
         passport_tmp.hcl = Some(String::from("#123abz"));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.ecl = Some(String::from("wat"));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
         
         passport_tmp = passport_valid.clone(); 
         passport_tmp.pid = Some(String::from("0123456789"));
-        assert_eq!(passport_tmp.is_valid_part2(), false);
+        assert!(!passport_tmp.is_valid_part2());
     }
 }
 
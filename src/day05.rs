@@ -1,92 +1,128 @@
-use std::fs::File;
-use std::path::Path;
-use std::io::{BufRead, BufReader};
+use std::collections::HashMap;
 
-pub fn run(input_path: &Path) -> bool {
-    let file = File::open(input_path).expect("Could not open file");
-    let reader = BufReader::new(&file);
+use super::diagnostic::Diagnostic;
+use super::error::{Error, Result};
+use super::solution::{Answer, Solution};
 
-    let lines = reader.lines();
-    let mut decoded_passes: Vec<BoardingPassDecoded> = lines
-        .map(|l| BoardingPass::new(&l.unwrap()).decode())
-        .collect();
+/// Day 5: Binary Boarding.
+pub struct Day05;
+impl Solution for Day05 {
+    type Input = Vec<BoardingPassDecoded>;
 
-    let max_id = decoded_passes.iter().map(|p| p.seat_id).max().unwrap();
-    println!("Part 1: Highest seat id: {}", max_id);
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut decoded_passes: Vec<BoardingPassDecoded> = input.lines()
+            .enumerate()
+            .map(|(i_line, l)| {
+                let num_chars = l.chars().count();
+                if num_chars > 10 {
+                    return Err(Diagnostic::new(i_line + 1, l, 11..num_chars + 1,
+                        format!("expected 10 characters, found {}", num_chars)).into());
+                }
+                BoardingPass::new(l).decode().map_err(|e| e.on_line(i_line + 1))
+            })
+            .collect::<Result<_>>()?;
+        let mut lines_by_seat = HashMap::new();
+        for (i_line, pass) in decoded_passes.iter().enumerate() {
+            if let Some(first_line) = lines_by_seat.insert(pass.seat_id, i_line + 1) {
+                return Err(Error::Validation(format!(
+                    "line {}: seat {} is already on line {}", i_line + 1, pass.seat_id, first_line)));
+            }
+        }
+        decoded_passes.sort_unstable_by_key(|p| p.seat_id);
+        Ok(decoded_passes)
+    }
 
-    // Part 2
-    // Missing boarding passes for rows at very front and back.
-    // My boarding pass is the only missing one.
+    fn part1(decoded_passes: &Self::Input) -> Result<Answer> {
+        let max_id = decoded_passes.iter().map(|p| p.seat_id).max()
+            .ok_or_else(|| Error::MissingAnswer(String::from("no boarding passes")))?;
+        Ok(max_id.into())
+    }
 
-    // Find missing bording pass with non-missing bording passes around it.
-    decoded_passes.sort_unstable_by(|a, b| a.seat_id.partial_cmp(&b.seat_id).unwrap());
-    let mut missing_seat_id = 0;
-    for (i, pass) in decoded_passes.iter().enumerate() {
-        if i > 0 {
-            let prev_seat_id = decoded_passes[i-1].seat_id;
-            let curr_seat_id = pass.seat_id;
+    fn part2(decoded_passes: &Self::Input) -> Result<Answer> {
+        // Missing boarding passes for rows at very front and back.
+        // My boarding pass is the only missing one.
 
-            if curr_seat_id - prev_seat_id != 1 {
-                missing_seat_id = prev_seat_id + 1;
-                break;
+        // Find missing bording pass with non-missing bording passes around it.
+        // The passes are sorted by seat id when parsed.
+        for (i, pass) in decoded_passes.iter().enumerate() {
+            if i > 0 {
+                let prev_seat_id = decoded_passes[i-1].seat_id;
+                let curr_seat_id = pass.seat_id;
+
+                if curr_seat_id - prev_seat_id != 1 {
+                    let missing_seat_id = prev_seat_id + 1;
+                    return Ok(missing_seat_id.into());
+                }
             }
         }
+        Err(Error::MissingAnswer(String::from("no seat is missing")))
     }
-    println!("Part 2: Missing seat id: {}", missing_seat_id);
-
-    true
 }
 
+/// A boarding pass in its binary space partitioning form, e.g. `FBFBBFFRLR`.
 #[derive(Debug, PartialEq)]
-struct BoardingPass {
+pub struct BoardingPass {
     passtr: [char; 10]
 }
 impl BoardingPass {
-    fn new(s: &str) -> BoardingPass { 
+    pub fn new(s: &str) -> BoardingPass { 
         let mut pass = BoardingPass{passtr: ['\0'; 10]};
         for (dest, src) in pass.passtr.iter_mut().zip(s.chars()) {
             *dest = src
         }
         pass
     }
-    fn decode(&self) -> BoardingPassDecoded { 
+    /// Decode the row, column and seat id.
+    ///
+    /// Errors are reported on line 1, with the column of the offending
+    /// character.
+    pub fn decode(&self) -> Result<BoardingPassDecoded> { 
         let mut rows = 0..128;
         let mut cols = 0..8;
         let rowstr = &self.passtr[0..7];
         let colstr = &self.passtr[7..10];
-        for c in rowstr.iter() {
+        for (i, c) in rowstr.iter().enumerate() {
             match c {
                 // Upper half
                 'B' => { rows.start += (rows.end - rows.start) / 2; },
                 // Lower half
                 'F' => { rows.end -= (rows.end - rows.start) / 2; },
-                _ => panic!("Unexpected character")
+                _ => return Err(self.unexpected_character(i, "F or B"))
             }
         }
-        for c in colstr.iter() {
+        for (i, c) in colstr.iter().enumerate() {
             match c {
                 // Upper half
                 'R' => { cols.start += (cols.end - cols.start) / 2; },
                 // Lower half
                 'L' => { cols.end -= (cols.end - cols.start) / 2; },
-                _ => panic!("Unexpected character")
+                _ => return Err(self.unexpected_character(7 + i, "L or R"))
             }
         }
         assert_eq!(rows.end - rows.start, 1);
         assert_eq!(cols.end - cols.start, 1);
-        BoardingPassDecoded {
+        Ok(BoardingPassDecoded {
             column: cols.start,
             row: rows.start,
             seat_id: rows.start * 8 + cols.start
-        }
+        })
+    }
+
+    fn unexpected_character(&self, position: usize, expected: &str) -> Error {
+        let source: String = self.passtr.iter().take_while(|&&c| c != '\0').collect();
+        let message = match self.passtr[position] {
+            '\0' => String::from("expected 10 characters"),
+            c => format!("unexpected character '{}', expected {}", c, expected),
+        };
+        Diagnostic::at(1, &source, position + 1, message).into()
     }
 }
 
 #[derive(Debug, PartialEq)]
-struct BoardingPassDecoded {
-    column: usize,
-    row: usize,
-    seat_id: usize
+pub struct BoardingPassDecoded {
+    pub column: usize,
+    pub row: usize,
+    pub seat_id: usize
 }
 
 #[cfg(test)]
@@ -95,14 +131,25 @@ mod tests {
 
     #[test]
     fn examples() {
-        assert_eq!(BoardingPass::new("FBFBBFFRLR").decode(),
+        assert_eq!(BoardingPass::new("FBFBBFFRLR").decode().unwrap(),
             BoardingPassDecoded{column: 5, row: 44, seat_id: 357 });
-        assert_eq!(BoardingPass::new("BFFFBBFRRR").decode(),
+        assert_eq!(BoardingPass::new("BFFFBBFRRR").decode().unwrap(),
             BoardingPassDecoded{column: 7, row: 70, seat_id: 567 });
-        assert_eq!(BoardingPass::new("FFFBBBFRRR").decode(),
+        assert_eq!(BoardingPass::new("FFFBBBFRRR").decode().unwrap(),
             BoardingPassDecoded{column: 7, row: 14, seat_id: 119 });
-        assert_eq!(BoardingPass::new("BBFFBBFRLL").decode(),
+        assert_eq!(BoardingPass::new("BBFFBBFRLL").decode().unwrap(),
             BoardingPassDecoded{column: 4, row: 102, seat_id: 820 });
+
+        assert!(BoardingPass::new("FBFBBFFRLX").decode().is_err());
+        assert!(BoardingPass::new("FBFBBFF").decode().is_err());
+    }
+
+    #[test]
+    fn duplicate_seats() {
+        match Day05::parse("FBFBBFFRLR\nFBFBBFFRRL\nFBFBBFFRLR\n") {
+            Err(Error::Validation(message)) => assert_eq!(message, "line 3: seat 357 is already on line 1"),
+            other => panic!("expected a duplicate seat, got {:?}", other),
+        }
     }
 }
 
@@ -1,28 +1,33 @@
-use std::path::Path;
 use std::collections::HashSet;
-use super::common;
+use super::diagnostic::Diagnostic;
+use super::error::Result;
+use super::solution::{Answer, Solution};
 
-pub fn run(input_path: &Path) -> bool {
-    let input = common::file_as_string(input_path);
-    
-    println!("Part1");
-    let declarations_part1 = parse_part1(input.as_str());
-    let sum: usize = declarations_part1.iter().map(|decl| decl.len()).sum();
-    println!("Sum: {}", sum);
-    assert_eq!(sum, 6443);
-    
-    println!("Part2");
-    let v = parse_part2(input.as_str());
-    let sum: u32 = v.iter().map(|decl| decl).sum();
-    println!("Sum: {}", sum);
-    assert_eq!(sum, 3232);
+/// Day 6: Custom Customs.
+pub struct Day06;
+impl Solution for Day06 {
+    type Input = (Vec<GroupDeclaration>, Vec<u32>);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok((parse_part1(input), parse_part2(input)?))
+    }
 
-    true
+    fn part1((declarations, _): &Self::Input) -> Result<Answer> {
+        let sum: usize = declarations.iter().map(|decl| decl.len()).sum();
+        Ok(sum.into())
+    }
+
+    fn part2((_, declarations): &Self::Input) -> Result<Answer> {
+        let sum: u32 = declarations.iter().sum();
+        Ok(sum.into())
+    }
 }
 
-type GroupDeclaration = HashSet<char>;
+/// The questions anyone in a group answered yes to.
+pub type GroupDeclaration = HashSet<char>;
 
-fn parse_part1(input: &str) -> Vec<GroupDeclaration> {
+/// Parse each group into the set of questions anyone answered yes to.
+pub fn parse_part1(input: &str) -> Vec<GroupDeclaration> {
     let mut declarations: Vec<GroupDeclaration> = Vec::new();
 
     for group_decl in input.split("\n\n") {
@@ -31,74 +36,67 @@ fn parse_part1(input: &str) -> Vec<GroupDeclaration> {
     declarations
 }
 
-fn parse_part2(input: &str) -> Vec<u32> {
+/// Count, per group, the questions everyone answered yes to.
+pub fn parse_part2(input: &str) -> Result<Vec<u32>> {
     let mut declarations: Vec<u32> = Vec::new();
 
+    let mut i_line = 0;
     for group_decl in input.split("\n\n") {
         // Within the group, how many questions did everyone answer yes to?
         // answers are from a-z. We need 26 positions -> 32 bits will do.
-        let group_bitflags = group_decl.lines()
-            .map(|l| {
-                // Turn individual char string into a 32bit bitfield
-                l.chars().fold(0u32, |mask, c| {
-                    assert!(('a'..='z').contains(&c));
-                    let mut b = [0; 1];
-                    c.encode_utf8(&mut b);
-                    let asci_count: u8 = b[0] - b'a';
-
-                    mask | (0b1 << asci_count)
-                })
-            })
-            .fold(0xffffffff, |mask, c| mask & c);
+        let mut group_bitflags = 0xffffffff;
+        for l in group_decl.lines() {
+            i_line += 1;
+            // Turn individual char string into a 32bit bitfield
+            let mut mask = 0u32;
+            for (i_c, c) in l.chars().enumerate() {
+                if !c.is_ascii_lowercase() {
+                    return Err(Diagnostic::at(i_line, l, i_c + 1,
+                        format!("unexpected character '{}', expected a-z", c)).into());
+                }
+                let mut b = [0; 1];
+                c.encode_utf8(&mut b);
+                let asci_count: u8 = b[0] - b'a';
+
+                mask |= 0b1 << asci_count;
+            }
+            group_bitflags &= mask;
+        }
+        // Account for the blank line separating groups.
+        i_line += 1;
 
         let num_yes = group_bitflags.count_ones();
         declarations.push(num_yes);
     }
-    declarations
+    Ok(declarations)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const EXAMPLE_INPUT: &str = "\
-abc
-
-a
-b
-c
-
-ab
-ac
-
-a
-a
-a
-a
-
-b";
+    const EXAMPLE_INPUT: &str = include_str!("../examples/day06/example.txt");
 
     #[test]
     fn example_part1() {
         let declarations = parse_part1(EXAMPLE_INPUT);
         for (i, dec) in declarations.iter().enumerate() {
-            println!("{}: {}", i, dec.len());
             match i {
-                0 | 1 | 2 => {
+                0..=2 => {
                     assert_eq!(dec.len(), 3);
-                    assert_eq!(dec.contains(&'a'), true);
-                    assert_eq!(dec.contains(&'b'), true);
-                    assert_eq!(dec.contains(&'c'), true);
-                    assert_eq!(dec.contains(&'d'), false);
+                    assert!(dec.contains(&'a'));
+                    assert!(dec.contains(&'b'));
+                    assert!(dec.contains(&'c'));
+                    assert!(!dec.contains(&'d'));
                 },
                 3 => {
                     assert_eq!(dec.len(), 1);
-                    assert_eq!(dec.contains(&'a'), true);
-                    assert_eq!(dec.contains(&'b'), false);
+                    assert!(dec.contains(&'a'));
+                    assert!(!dec.contains(&'b'));
                 },
                 4 => {
                     assert_eq!(dec.len(), 1);
-                    assert_eq!(dec.contains(&'a'), false);
-                    assert_eq!(dec.contains(&'b'), true);
+                    assert!(!dec.contains(&'a'));
+                    assert!(dec.contains(&'b'));
                 },
                 _ => { panic!("Unexpected group"); }
             }
@@ -110,9 +108,8 @@ b";
     
     #[test]
     fn example_part2() {
-        let declarations = parse_part2(EXAMPLE_INPUT);
+        let declarations = parse_part2(EXAMPLE_INPUT).unwrap();
         for (i, dec) in declarations.iter().enumerate() {
-            println!("{}: {}", i, dec);
             match i {
                 0 => {
                     assert_eq!(dec, &3);
@@ -130,7 +127,7 @@ b";
             }
         }
 
-        let sum: u32 = declarations.iter().map(|decl| decl).sum();
+        let sum: u32 = declarations.iter().sum();
         assert_eq!(6, sum);
     }
 }
@@ -1,74 +1,132 @@
-use std::path::Path;
 use std::collections::{HashSet, HashMap};
-use super::common;
-
-pub fn run(input_path: &Path) -> bool {
-    let input = common::file_as_string(input_path);
-    let (num_bags_contained, num_types_that_can_contain) = calc_bags(&input);
-    assert_eq!(num_types_that_can_contain, 128);
-    assert_eq!(num_bags_contained, 20189);
-    true
+use super::diagnostic::Diagnostic;
+use super::error::{Error, Result};
+use super::solution::{Answer, Solution};
+
+/// The bag colour the puzzle asks about.
+pub const LOOK_FOR_KEY: &str = "shiny gold";
+
+/// Day 7: Handy Haversacks.
+pub struct Day07;
+impl Solution for Day07 {
+    type Input = BagRules;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(rules: &Self::Input) -> Result<Answer> {
+        let num_types_that_can_contain = count_types_that_can_contain(rules, LOOK_FOR_KEY);
+        Ok(num_types_that_can_contain.into())
+    }
+
+    fn part2(rules: &Self::Input) -> Result<Answer> {
+        let num_bags_contained = count_bags_contained(rules, LOOK_FOR_KEY)?;
+        Ok(num_bags_contained.into())
+    }
+}
+
+/// The bag rules, indexed both by outer and by inner bag colour.
+#[derive(Debug, Default)]
+pub struct BagRules {
+    contains: HashMap<String, Vec<(usize, String)>>,
+    is_contained_in: HashMap<String, Vec<String>>,
 }
 
-fn calc_bags(input: &str) -> (usize, usize) {
-    let mut bag_map_contains: HashMap<&str, Vec<(usize, &str)>> = HashMap::new();
-    let mut bag_map_is_contained_in: HashMap<&str, Vec<&str>> = HashMap::new();
-    for line in input.lines() {
-        let mut spec_split = line.split(" bags contain ");
-        let bag_key = spec_split.next().unwrap();
-        let bag_contains = spec_split.next().unwrap().split(", ");
+/// Parse one rule per line.
+pub fn parse(input: &str) -> Result<BagRules> {
+    let mut rules = BagRules::default();
+    for (i_line, line) in input.lines().enumerate() {
+        let err = |part: &str, message: &str| Error::from(Diagnostic::spanning(i_line + 1, line, part, message));
+        let (bag_key, bag_contains) = line.split_once(" bags contain ")
+            .ok_or_else(|| err(line, "expected '<colour> bags contain <contents>'"))?;
+        let bag_contains = bag_contains.split(", ");
         
         for b in bag_contains {
-            let b = b.trim_end_matches(".").trim_end_matches(" bag").trim_end_matches(" bags");
+            let b = b.trim_end_matches('.').trim_end_matches(" bag").trim_end_matches(" bags");
             if b != "no other" {
-                let n: usize = b.matches(char::is_numeric).nth(0).unwrap().parse().unwrap();
-                let b = b.trim_start_matches(|c: char| c.is_numeric() || c == ' '); // remove number and space
+                // remove number and space
+                let (n, b) = b.split_once(' ')
+                    .ok_or_else(|| err(b, "expected '<count> <colour> bags'"))?;
+                let n: usize = n.parse()
+                    .map_err(|e| err(n, &format!("invalid bag count '{}': {}", n, e)))?;
                 
-                match bag_map_contains.get_mut(bag_key) {
-                    Some(v) => v.push((n, b)),
-                    None => { bag_map_contains.insert(bag_key, vec![(n, b)]); }
-                }
+                rules.contains.entry(bag_key.to_string())
+                    .or_default()
+                    .push((n, b.to_string()));
 
                 let is_contained_in = bag_key;
-                match bag_map_is_contained_in.get_mut(b) {
-                    Some(v) => v.push(is_contained_in),
-                    None => { bag_map_is_contained_in.insert(b, vec![is_contained_in]); }
-                }
+                rules.is_contained_in.entry(b.to_string())
+                    .or_default()
+                    .push(is_contained_in.to_string());
             }
         }
     }
 
+    Ok(rules)
+}
+
+/// Count the bag colours that can eventually contain a `look_for_key` bag.
+pub fn count_types_that_can_contain(rules: &BagRules, look_for_key: &str) -> usize {
     let mut types: HashSet<&str> = HashSet::new();
-    let look_for_key = "shiny gold";
     let mut new_types_stack: Vec<&str> = vec![look_for_key];
     while let Some(k) = new_types_stack.pop() {
-        if let Some(vec) = bag_map_is_contained_in.get(k) {
+        if let Some(vec) = rules.is_contained_in.get(k) {
             for e in vec { 
-                types.insert(e); 
-                new_types_stack.push(e);
+                if types.insert(e) {
+                    new_types_stack.push(e);
+                }
             } 
         }
     }
 
-    println!("Number of bag types that can contain at least one {}: {}", look_for_key, types.len());
+    types.len()
+}
 
-    let mut bag_count = 0;
-    let mut search_stack: Vec<(usize, &str)> = vec![(1, look_for_key)];
-    while let Some((n_search, k_search)) = search_stack.pop() {
-        if let Some(vec) = bag_map_contains.get(k_search) {
-            for (n, k) in vec {
-                bag_count += n_search*n;
-                search_stack.push((n_search*n, k));
+/// Count the bags required inside a single `look_for_key` bag.
+///
+/// Every colour is counted once, depth first. Rules where a bag eventually
+/// contains itself are an error, as is a count that does not fit in a `u64`.
+pub fn count_bags_contained(rules: &BagRules, look_for_key: &str) -> Result<u64> {
+    let overflow = || Error::Overflow(format!("bags inside a {} bag", look_for_key));
+    // Bags inside one bag of every colour counted so far.
+    let mut inside: HashMap<&str, u64> = HashMap::new();
+    // Colours whose contents are still being counted.
+    let mut visiting: HashSet<&str> = HashSet::new();
+    // A colour is pushed unexpanded, then again to be summed once every
+    // colour inside it has been counted.
+    let mut search_stack: Vec<(&str, bool)> = vec![(look_for_key, false)];
+    while let Some((k_search, expanded)) = search_stack.pop() {
+        if inside.contains_key(k_search) {
+            continue;
+        }
+        let contents = rules.contains.get(k_search).map_or(&[][..], Vec::as_slice);
+        if expanded {
+            let mut bag_count: u64 = 0;
+            for (n, k) in contents {
+                let bags = inside[k.as_str()].checked_add(1)
+                    .and_then(|b| b.checked_mul(*n as u64))
+                    .ok_or_else(overflow)?;
+                bag_count = bag_count.checked_add(bags).ok_or_else(overflow)?;
+            }
+            visiting.remove(k_search);
+            inside.insert(k_search, bag_count);
+        } else {
+            if !visiting.insert(k_search) {
+                return Err(Error::Validation(format!("the bag rules are cyclic: {} bags eventually contain themselves", k_search)));
+            }
+            search_stack.push((k_search, true));
+            for (_, k) in contents {
+                if !inside.contains_key(k.as_str()) {
+                    search_stack.push((k, false));
+                }
             }
         }
     }
-    println!("Number of bags that 1 {} bag contains: {}", look_for_key, bag_count);
-
 
-    return (bag_count, types.len());
+    Ok(inside[look_for_key])
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,10 +144,33 @@ faded blue bags contain no other bags.
 dotted black bags contain no other bags.
 ";
 
-        let (num_bags_contained, num_types_that_can_contain) = calc_bags(example_str);
+        let rules = parse(example_str).unwrap();
+        let num_types_that_can_contain = count_types_that_can_contain(&rules, LOOK_FOR_KEY);
+        let num_bags_contained = count_bags_contained(&rules, LOOK_FOR_KEY).unwrap();
         assert_eq!(num_types_that_can_contain, 4);
         assert_eq!(num_bags_contained, 32);
 
     }
+
+    #[test]
+    fn cycles_and_overflow_are_errors() {
+        let rules = parse("shiny gold bags contain 1 shiny gold bag.").unwrap();
+        assert!(matches!(count_bags_contained(&rules, LOOK_FOR_KEY), Err(Error::Validation(_))));
+
+        let rules = parse("shiny gold bags contain 2 dark red bags.\n\
+                           dark red bags contain 1 pale blue bag.\n\
+                           pale blue bags contain 3 shiny gold bags.").unwrap();
+        assert!(matches!(count_bags_contained(&rules, LOOK_FOR_KEY), Err(Error::Validation(_))));
+
+        // Shared contents are not a cycle.
+        let rules = parse("shiny gold bags contain 1 dark red bag, 2 pale blue bags.\n\
+                           dark red bags contain 3 pale blue bags.\n\
+                           pale blue bags contain no other bags.").unwrap();
+        assert_eq!(count_bags_contained(&rules, LOOK_FOR_KEY).unwrap(), 1 + 3 + 2);
+
+        let rules = parse("shiny gold bags contain 99999999999 dark red bags.\n\
+                           dark red bags contain 99999999999 pale blue bags.").unwrap();
+        assert!(matches!(count_bags_contained(&rules, LOOK_FOR_KEY), Err(Error::Overflow(_))));
+    }
 }
 
@@ -0,0 +1,116 @@
+use std::fmt;
+use std::ops::Range;
+
+/// A problem in an input file, pointing at a span of a single source line.
+///
+/// Renders like a compiler error, with the offending span underlined:
+///
+/// ```text
+/// error: unexpected character 'X', expected F or B
+///  --> input/day05.txt:2:7
+///   |
+/// 2 | FBFBBFXRLR
+///   |       ^
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub file: Option<String>,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based, end-exclusive character columns.
+    pub columns: Range<usize>,
+    pub source_line: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// A diagnostic for `columns` of `source_line`. Empty ranges are widened
+    /// to a single column so there is always something to point at.
+    pub fn new<S: Into<String>>(line: usize, source_line: &str, columns: Range<usize>, message: S) -> Diagnostic {
+        let columns = if columns.end > columns.start { columns } else { columns.start..columns.start + 1 };
+        Diagnostic {
+            file: None,
+            line,
+            columns,
+            source_line: String::from(source_line),
+            message: message.into(),
+        }
+    }
+
+    /// A diagnostic for `part`, which must be a subslice of `source_line`.
+    /// Debug builds check that it is; release builds clamp it to the line.
+    pub fn spanning<S: Into<String>>(line: usize, source_line: &str, part: &str, message: S) -> Diagnostic {
+        let source_start = source_line.as_ptr() as usize;
+        let part_start = part.as_ptr() as usize;
+        debug_assert!(part_start >= source_start && part_start + part.len() <= source_start + source_line.len(),
+                      "{:?} is not a subslice of {:?}", part, source_line);
+        let offset = part_start.saturating_sub(source_start).min(source_line.len());
+        let start = source_line[..offset].chars().count() + 1;
+        let end = start + part.chars().count();
+        Diagnostic::new(line, source_line, start..end, message)
+    }
+
+    /// A diagnostic for the whole of `source_line`.
+    pub fn whole_line<S: Into<String>>(line: usize, source_line: &str, message: S) -> Diagnostic {
+        Diagnostic::spanning(line, source_line, source_line, message)
+    }
+
+    /// A diagnostic for the single character at 1-based `column`.
+    pub fn at<S: Into<String>>(line: usize, source_line: &str, column: usize, message: S) -> Diagnostic {
+        Diagnostic::new(line, source_line, column..column + 1, message)
+    }
+
+    pub fn column(&self) -> usize {
+        self.columns.start
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f, "error: {}", self.message)?;
+        writeln!(f, "{}--> {}:{}:{}", gutter,
+                 self.file.as_deref().unwrap_or("<input>"), self.line, self.column())?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{} | {}{}", gutter,
+               " ".repeat(self.columns.start - 1),
+               "^".repeat(self.columns.end - self.columns.start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let line = "1-3 a abcde";
+        let mut d = Diagnostic::spanning(12, line, &line[4..5], "expected ':'");
+        d.file = Some(String::from("input/day02.txt"));
+        assert_eq!(d.columns, 5..6);
+        assert_eq!(d.to_string(), "\
+error: expected ':'
+  --> input/day02.txt:12:5
+   |
+12 | 1-3 a abcde
+   |     ^");
+    }
+
+    #[test]
+    fn spans() {
+        let line = "ab\u{e9}cd";
+        assert_eq!(Diagnostic::spanning(1, line, &line[4..], "").columns, 4..6);
+        assert_eq!(Diagnostic::spanning(1, line, &line[6..], "").columns, 6..7);
+        assert_eq!(Diagnostic::whole_line(1, line, "").columns, 1..6);
+        assert_eq!(Diagnostic::whole_line(1, "", "").columns, 1..2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "is not a subslice")]
+    fn spanning_checks_the_part() {
+        let line = String::from("1-3 a: abcde");
+        Diagnostic::spanning(1, &line, "abcde", "");
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+use std::io;
+
+use super::diagnostic::Diagnostic;
+
+/// Errors from reading, parsing and solving puzzles.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// Malformed input.
+    Parse(Diagnostic),
+    /// Input that is well-formed but cannot be solved as given.
+    Validation(String),
+    /// The input has no answer for a part.
+    MissingAnswer(String),
+    /// A request to the puzzle website failed.
+    Http(String),
+    /// A computation does not fit the integer type it is done in.
+    Overflow(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Move a parse error of a single line to line `line` of a larger input.
+    pub fn on_line(self, line: usize) -> Error {
+        match self {
+            Error::Parse(d) => Error::Parse(Diagnostic { line, ..d }),
+            e => e,
+        }
+    }
+
+    /// Name the file a parse error was found in.
+    pub fn in_file<S: Into<String>>(self, file: S) -> Error {
+        match self {
+            Error::Parse(d) => Error::Parse(Diagnostic { file: Some(file.into()), ..d }),
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Parse(d) => write!(f, "{}", d),
+            Error::Validation(message) => write!(f, "invalid input: {}", message),
+            Error::MissingAnswer(message) => write!(f, "no answer: {}", message),
+            Error::Http(message) => write!(f, "HTTP error: {}", message),
+            Error::Overflow(message) => write!(f, "arithmetic overflow: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<Diagnostic> for Error {
+    fn from(d: Diagnostic) -> Self {
+        Error::Parse(d)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,144 @@
+//! Puzzle examples stored as data files.
+//!
+//! Every day may have a directory `examples/dayNN/` of example inputs
+//! `<name>.txt`, each with a sidecar `<name>.toml` of expected answers:
+//!
+//! ```toml
+//! part1 = 514579
+//! ```
+//!
+//! A part missing from the sidecar is not checked for that example.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::answers::parse_answer_line;
+use super::common;
+use super::error::{Error, Result};
+use super::solution::{Answer, Day};
+
+/// Default directory of the example files, relative to the working directory.
+pub const DEFAULT_DIR: &str = "examples";
+
+/// An example input with the answers it should produce.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub name: String,
+    pub input: String,
+    pub expected: [Option<Answer>; 2],
+}
+
+/// Parse a sidecar of expected answers.
+pub fn parse_expected(sidecar: &str) -> Result<[Option<Answer>; 2]> {
+    let mut expected = [None, None];
+    for (i_line, source_line) in sidecar.lines().enumerate() {
+        let line = source_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (part, answer) = parse_answer_line(i_line + 1, source_line)?;
+        expected[part as usize - 1] = Some(answer);
+    }
+    Ok(expected)
+}
+
+/// The directory holding the examples of `day` inside `dir`.
+pub fn day_dir(dir: &Path, day: u32) -> PathBuf {
+    dir.join(format!("day{:02}", day))
+}
+
+/// Load the examples of `day` from `dir`, sorted by name. A day without an
+/// examples directory has no examples.
+pub fn load(dir: &Path, day: u32) -> Result<Vec<Example>> {
+    let entries = match fs::read_dir(day_dir(dir, day)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::Io(e)),
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "txt") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut examples = Vec::new();
+    for path in paths {
+        let sidecar_path = path.with_extension("toml");
+        let sidecar = fs::read_to_string(&sidecar_path).map_err(|e| {
+            Error::Validation(format!("could not read {}: {}", sidecar_path.display(), e))
+        })?;
+        examples.push(Example {
+            name: path.file_stem().unwrap().to_string_lossy().into_owned(),
+            input: common::file_as_string(&path)?,
+            expected: parse_expected(&sidecar).map_err(|e| e.in_file(sidecar_path.display().to_string()))?,
+        });
+    }
+    Ok(examples)
+}
+
+/// The result of one part of one example.
+#[derive(Debug)]
+pub struct Check {
+    pub example: String,
+    pub part: u8,
+    pub expected: Answer,
+    pub actual: Result<Answer>,
+}
+
+impl Check {
+    pub fn passed(&self) -> bool {
+        matches!(&self.actual, Ok(a) if *a == self.expected)
+    }
+}
+
+/// Solve every part of `examples` that has an expected answer.
+pub fn check(day: &Day, examples: &[Example]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for example in examples {
+        for (part, expected) in (1..=2).zip(&example.expected) {
+            if let Some(expected) = expected {
+                checks.push(Check {
+                    example: example.name.clone(),
+                    part,
+                    expected: expected.clone(),
+                    actual: (day.solve_part)(&example.input, part),
+                });
+            }
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_day;
+
+    #[test]
+    fn sidecar() {
+        assert_eq!(parse_expected("# only part 2\npart2 = \"abc\"\n").unwrap(),
+                   [None, Some(Answer::Text(String::from("abc")))]);
+        assert!(parse_expected("part3 = 1").is_err());
+    }
+
+    #[test]
+    fn checks_only_expected_parts() {
+        let example = |part1, part2| Example {
+            name: String::from("example"),
+            input: String::from("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc"),
+            expected: [part1, part2],
+        };
+        let day = find_day(2).unwrap();
+        let checks = check(day, &[example(Some(Answer::Number(2)), None)]);
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].passed());
+
+        let checks = check(day, &[example(None, Some(Answer::Number(2)))]);
+        assert_eq!(checks[0].part, 2);
+        assert!(!checks[0].passed());
+    }
+}
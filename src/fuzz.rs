@@ -0,0 +1,99 @@
+//! Entry points for fuzzing the parsers of untrusted input.
+//!
+//! Every target takes arbitrary bytes and must return without panicking:
+//! input that is not UTF-8 or not well formed is an error. The module is only
+//! built with the `fuzz` feature, which `tests/parsers.rs` enables to run the
+//! targets on mutated examples with proptest, and the `fuzz/` crate to run
+//! them under libFuzzer:
+//!
+//! ```text
+//! cargo +nightly fuzz run day02
+//! ```
+
+use std::str;
+
+use super::answers::ExpectedAnswers;
+use super::bench::Baseline;
+use super::day02::{self, Unit};
+use super::day04;
+use super::day05::BoardingPass;
+use super::error::{Error, Result};
+use super::examples;
+use super::solution::Solution;
+use super::submit::{self, SubmissionLog};
+use super::{day01, day03, day05, day06, day07};
+
+/// A named parser to fuzz.
+pub struct Target {
+    pub name: &'static str,
+    parse: fn(&[u8]) -> Result<()>,
+}
+
+impl Target {
+    /// Parse `data`, returning the rendered error if it is rejected.
+    pub fn run(&self, data: &[u8]) -> std::result::Result<(), String> {
+        (self.parse)(data).map_err(|e| e.to_string())
+    }
+}
+
+/// Every fuzz target, named like the files in `fuzz/fuzz_targets/`.
+pub const TARGETS: &[Target] = &[
+    Target { name: "day01", parse: solution::<day01::Day01> },
+    Target { name: "day02", parse: day02_records },
+    Target { name: "day02_policy", parse: day02_policy },
+    Target { name: "day03", parse: solution::<day03::Day03> },
+    Target { name: "day04", parse: |data| day04::parse(data).map(drop) },
+    Target { name: "day05", parse: solution::<day05::Day05> },
+    Target { name: "day05_pass", parse: |data| BoardingPass::new(text(data)?).decode().map(drop) },
+    Target { name: "day06", parse: solution::<day06::Day06> },
+    Target { name: "day07", parse: solution::<day07::Day07> },
+    Target { name: "answers", parse: |data| ExpectedAnswers::parse(text(data)?).map(drop) },
+    Target { name: "example_sidecar", parse: |data| examples::parse_expected(text(data)?).map(drop) },
+    Target { name: "bench_baseline", parse: |data| Baseline::parse(text(data)?).map(drop) },
+    Target { name: "submission_log", parse: submission_log },
+];
+
+/// The target called `name`.
+pub fn target(name: &str) -> Option<&'static Target> {
+    TARGETS.iter().find(|t| t.name == name)
+}
+
+fn text(data: &[u8]) -> Result<&str> {
+    str::from_utf8(data).map_err(|e| Error::Validation(format!("input is not UTF-8: {}", e)))
+}
+
+fn solution<S: Solution>(data: &[u8]) -> Result<()> {
+    S::parse(text(data)?).map(drop)
+}
+
+/// Parse records straight from the bytes and check them against both
+/// policies in every unit, where out-of-range positions used to panic.
+fn day02_records(data: &[u8]) -> Result<()> {
+    for record in day02::records(data) {
+        let record = record?;
+        for unit in [Unit::Byte, Unit::Char, Unit::Grapheme] {
+            day02::check_policy1(&record, unit);
+            day02::check_policy2(&record, unit);
+        }
+    }
+    Ok(())
+}
+
+/// Parse the first line as a policy and audit the rest with it.
+fn day02_policy(data: &[u8]) -> Result<()> {
+    let text = text(data)?;
+    let (expr, records) = text.split_once('\n').unwrap_or((text, ""));
+    let policy = day02::parse_policy(expr, Unit::Grapheme)?;
+    day02::audit(day02::records(records.as_bytes()), &*policy)?.render(super::output::Format::Json);
+    Ok(())
+}
+
+fn submission_log(data: &[u8]) -> Result<()> {
+    let log = SubmissionLog::parse(text(data)?);
+    submit::parse_response(text(data)?);
+    for attempt in &log.attempts {
+        let _ = log.check(attempt.day, attempt.part, &attempt.answer, attempt.time);
+    }
+    assert_eq!(SubmissionLog::parse(&log.to_text()), log, "log does not round trip");
+    Ok(())
+}
@@ -0,0 +1,351 @@
+//! A minimal HTTP/1.1 client, just enough to talk to the Advent of Code site.
+//!
+//! Plain `http://` URLs are handled over a `TcpStream`, which is what local
+//! test servers use. `https://` requests, which includes everything sent to
+//! the real site, are delegated to the `curl` binary, so `curl` must be on
+//! the `PATH` to fetch inputs or submit answers.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use super::error::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// How long a request may take to connect, and then to send or receive.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Send a request with `method` to `url` and wait for the whole response.
+/// Header names and values may not contain control characters.
+pub fn request(method: &str, url: &str, headers: &[(&str, &str)], body: Option<&str>) -> Result<Response> {
+    if let Some((name, _)) = headers.iter().find(|(name, value)| has_control(name) || has_control(value)) {
+        return Err(Error::Http(format!("header '{}' contains a control character", name.escape_debug())));
+    }
+    if let Some(rest) = url.strip_prefix("http://") {
+        request_plain(method, rest, headers, body)
+    } else if url.starts_with("https://") {
+        request_curl(method, url, headers, body)
+    } else {
+        Err(Error::Http(format!("unsupported URL '{}'", url)))
+    }
+}
+
+pub fn get(url: &str, headers: &[(&str, &str)]) -> Result<Response> {
+    request("GET", url, headers, None)
+}
+
+pub fn post(url: &str, headers: &[(&str, &str)], body: &str) -> Result<Response> {
+    request("POST", url, headers, Some(body))
+}
+
+fn request_plain(method: &str, host_and_path: &str, headers: &[(&str, &str)], body: Option<&str>) -> Result<Response> {
+    let (host, path) = match host_and_path.find('/') {
+        Some(i) => (&host_and_path[..i], &host_and_path[i..]),
+        None => (host_and_path, "/"),
+    };
+    let address = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+
+    let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", method, path, host);
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if let Some(body) = body {
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    request.push_str("\r\n");
+    request.push_str(body.unwrap_or(""));
+
+    let mut stream = connect(&address)?;
+    stream.write_all(request.as_bytes())?;
+    read_response(BufReader::new(stream))
+}
+
+/// Connect to the first address `address` resolves to that answers within
+/// [`TIMEOUT`], and time out reads and writes on the stream just as long.
+fn connect(address: &str) -> Result<TcpStream> {
+    let mut last_error = None;
+    for socket_address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&socket_address, TIMEOUT) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(TIMEOUT))?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(match last_error {
+        Some(e) => e.into(),
+        None => Error::Http(format!("'{}' did not resolve to any address", address)),
+    })
+}
+
+fn has_control(s: &str) -> bool {
+    s.chars().any(char::is_control)
+}
+
+/// The status line and the framing headers of a response.
+struct Head {
+    status: u16,
+    content_length: Option<usize>,
+    chunked: bool,
+}
+
+fn read_head<R: BufRead>(reader: &mut R) -> Result<Head> {
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = status_line.split_whitespace().nth(1)
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| Error::Http(format!("malformed status line '{}'", status_line.trim_end())))?;
+
+    let mut content_length = None;
+    let mut chunked = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse::<usize>().ok();
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            }
+        }
+    }
+    Ok(Head { status, content_length, chunked })
+}
+
+fn read_response<R: BufRead>(mut reader: R) -> Result<Response> {
+    let Head { status, content_length, chunked } = read_head(&mut reader)?;
+    let mut body = Vec::new();
+    if chunked {
+        loop {
+            let mut size_line = String::new();
+            reader.read_line(&mut size_line)?;
+            let size_str = size_line.trim().split(';').next().unwrap_or("");
+            let size = usize::from_str_radix(size_str, 16)
+                .map_err(|_| Error::Http(format!("malformed chunk size '{}'", size_line.trim())))?;
+            if size == 0 {
+                break;
+            }
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk)?;
+            body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(length) = content_length {
+        body.resize(length, 0);
+        reader.read_exact(&mut body)?;
+    } else {
+        reader.read_to_end(&mut body)?;
+    }
+
+    into_response(status, body)
+}
+
+fn into_response(status: u16, body: Vec<u8>) -> Result<Response> {
+    let body = String::from_utf8(body)
+        .map_err(|_| Error::Http(String::from("response body is not valid UTF-8")))?;
+    Ok(Response { status, body })
+}
+
+/// Quote `s` as a string in a curl config file. Line breaks and tabs are
+/// escaped; any other control character would end or change the option, so
+/// it is an error.
+fn quote(s: &str) -> Result<String> {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                return Err(Error::Http(format!("cannot pass {:?} to curl", c)));
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Ok(quoted)
+}
+
+/// A curl config file passing `headers` and `body`. The headers hold the
+/// session cookie, so they must not appear on the command line where other
+/// users can see them.
+fn curl_config(headers: &[(&str, &str)], body: Option<&str>) -> Result<String> {
+    let mut config = String::new();
+    for (name, value) in headers {
+        config.push_str(&format!("header = {}\n", quote(&format!("{}: {}", name, value))?));
+    }
+    if let Some(body) = body {
+        config.push_str(&format!("data-binary = {}\n", quote(body)?));
+    }
+    Ok(config)
+}
+
+/// What curl appends to the body: a line with the status of the last
+/// response, after any interim `1xx` or proxy tunnel responses.
+const CURL_WRITE_OUT: &str = "\n%{http_code}";
+
+/// Split the output of curl into the body and the status it wrote after it
+/// with [`CURL_WRITE_OUT`]. curl has already removed the transfer encoding.
+fn parse_curl_output(mut output: Vec<u8>) -> Result<Response> {
+    let newline = output.iter().rposition(|&b| b == b'\n')
+        .ok_or_else(|| Error::Http(String::from("curl did not report a status")))?;
+    let status = std::str::from_utf8(&output[newline + 1..]).ok()
+        .and_then(|s| s.parse::<u16>().ok())
+        .filter(|&status| status != 0)
+        .ok_or_else(|| Error::Http(format!("curl reported a malformed status '{}'",
+                                           String::from_utf8_lossy(&output[newline + 1..]))))?;
+    output.truncate(newline);
+    into_response(status, output)
+}
+
+fn request_curl(method: &str, url: &str, headers: &[(&str, &str)], body: Option<&str>) -> Result<Response> {
+    let config = curl_config(headers, body)?;
+    let max_time = TIMEOUT.as_secs().to_string();
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--max-time", &max_time, "--write-out", CURL_WRITE_OUT,
+               "--request", method, "--config", "-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => Error::Http(String::from(
+                "https requests need curl, but it was not found on the PATH")),
+            _ => Error::Http(format!("could not run curl for an https request: {}", e)),
+        })?;
+    child.stdin.take().unwrap().write_all(config.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::Http(format!("curl failed: {}", String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    parse_curl_output(output.stdout)
+}
+
+/// A one-shot HTTP server on localhost for tests.
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    /// Serve `responses` in order, one per connection, and return the base
+    /// URL together with a handle yielding the raw requests received.
+    pub fn serve(responses: Vec<(u16, String)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = v.trim().parse().unwrap();
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" { break; }
+                }
+                let mut body_in = vec![0; content_length];
+                reader.read_exact(&mut body_in).unwrap();
+                request.push_str(&String::from_utf8(body_in).unwrap());
+                requests.push(request);
+
+                let mut stream = reader.into_inner();
+                write!(stream, "HTTP/1.1 {} OK\r\nContent-Length: {}\r\n\r\n{}", status, body.len(), body).unwrap();
+            }
+            requests
+        });
+        (base_url, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_post() {
+        let (base_url, server) = mock::serve(vec![
+            (200, String::from("hello")),
+            (404, String::from("missing")),
+        ]);
+        let response = get(&format!("{}/a/b", base_url), &[("Cookie", "session=x")]).unwrap();
+        assert_eq!(response, Response { status: 200, body: String::from("hello") });
+        let response = post(&format!("{}/c", base_url), &[], "level=1").unwrap();
+        assert_eq!(response.status, 404);
+        assert!(!response.is_success());
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /a/b HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Cookie: session=x\r\n"));
+        assert!(requests[1].starts_with("POST /c HTTP/1.1\r\n"));
+        assert!(requests[1].ends_with("\r\n\r\nlevel=1"));
+    }
+
+    #[test]
+    fn chunked_body() {
+        let raw = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n";
+        let response = read_response(raw.as_bytes()).unwrap();
+        assert_eq!(response.body, "abcde");
+    }
+
+    #[test]
+    fn curl_output() {
+        let response = parse_curl_output(b"plain body\nline 2\n\n200".to_vec()).unwrap();
+        assert_eq!(response, Response { status: 200, body: String::from("plain body\nline 2\n") });
+
+        // A body that looks like a header block is still the body.
+        let raw = "HTTP/1.1 200 OK\r\n\r\nHTTP/ in the body\n302";
+        let response = parse_curl_output(raw.as_bytes().to_vec()).unwrap();
+        assert_eq!(response, Response { status: 302, body: String::from("HTTP/1.1 200 OK\r\n\r\nHTTP/ in the body") });
+
+        assert!(matches!(parse_curl_output(b"\n000".to_vec()), Err(Error::Http(_))));
+        assert!(matches!(parse_curl_output(b"no status".to_vec()), Err(Error::Http(_))));
+    }
+
+    #[test]
+    fn curl_config_quotes_values() {
+        assert_eq!(curl_config(&[("Cookie", "session=a\"b\\c")], Some("level=1&answer=x")).unwrap(),
+                   "header = \"Cookie: session=a\\\"b\\\\c\"\ndata-binary = \"level=1&answer=x\"\n");
+        assert_eq!(curl_config(&[], None).unwrap(), "");
+
+        // A line break cannot start a new option.
+        assert_eq!(curl_config(&[], Some("a\nurl = \"x\"\r")).unwrap(),
+                   "data-binary = \"a\\nurl = \\\"x\\\"\\r\"\n");
+        assert!(matches!(curl_config(&[], Some("a\u{0}b")), Err(Error::Http(_))));
+    }
+
+    #[test]
+    fn control_characters_in_headers() {
+        for header in [("Cookie", "session=x\r\nHost: evil"), ("X\nY", "1")] {
+            assert!(matches!(get("http://127.0.0.1:1/", &[header]), Err(Error::Http(_))));
+        }
+    }
+
+    #[test]
+    fn unsupported_scheme() {
+        assert!(matches!(get("ftp://example.com", &[]), Err(Error::Http(_))));
+    }
+}
@@ -0,0 +1,50 @@
+//! Solutions to Advent of Code 2020.
+//!
+//! Every day lives in its own module and implements [`Solution`]. The
+//! [`DAYS`] registry lists them all, so a day can be run by number without
+//! naming its type.
+
+pub mod answers;
+pub mod bench;
+pub mod client;
+pub mod common;
+pub mod diagnostic;
+pub mod error;
+pub mod examples;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod http;
+pub mod output;
+pub mod rng;
+pub mod runner;
+pub mod scaffold;
+pub mod solution;
+pub mod submit;
+pub mod watch;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+
+pub use diagnostic::Diagnostic;
+pub use error::{Error, Result};
+pub use solution::{Answer, Answers, Day, Run, Solution, Timings, solve, solve_part};
+
+/// All registered days, in order.
+pub const DAYS: &[Day] = &[
+    Day { number: 1, solve: solve::<day01::Day01>, solve_part: solve_part::<day01::Day01> },
+    Day { number: 2, solve: solve::<day02::Day02>, solve_part: solve_part::<day02::Day02> },
+    Day { number: 3, solve: solve::<day03::Day03>, solve_part: solve_part::<day03::Day03> },
+    Day { number: 4, solve: solve::<day04::Day04>, solve_part: solve_part::<day04::Day04> },
+    Day { number: 5, solve: solve::<day05::Day05>, solve_part: solve_part::<day05::Day05> },
+    Day { number: 6, solve: solve::<day06::Day06>, solve_part: solve_part::<day06::Day06> },
+    Day { number: 7, solve: solve::<day07::Day07>, solve_part: solve_part::<day07::Day07> },
+];
+
+/// Look up a registered day by its number.
+pub fn find_day(number: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.number == number)
+}
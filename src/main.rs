@@ -1,29 +1,690 @@
 use std::env;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
 
-pub mod common;
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
+use adventofcode2020::{answers, bench, client, common, day01, day02, day03, examples, find_day, runner, scaffold, solution, submit, watch};
+use adventofcode2020::answers::{ExpectedAnswers, Verdict};
+use adventofcode2020::client::{Client, Fetched};
+use adventofcode2020::output::{self, Format, Report};
+use adventofcode2020::submit::Outcome;
+use adventofcode2020::Error;
+
+fn usage() {
+    eprintln!("usage: adventofcode2020 <day | first-last | all> [--format json|csv|text] [--record]");
+    eprintln!("                        [--input <path | ->]");
+    eprintln!("       adventofcode2020 fetch <day | first-last | all>");
+    eprintln!("       adventofcode2020 submit <day> <1 | 2>");
+    eprintln!("       adventofcode2020 new <day> [title]");
+    eprintln!("       adventofcode2020 bench <day | first-last | all> [--iterations N]");
+    eprintln!("                        [--save-baseline[=path]] [--compare[=path]] [--threshold PCT]");
+    eprintln!("       adventofcode2020 watch <day> [--interval MS]");
+    eprintln!("       adventofcode2020 test <day | first-last | all>");
+    eprintln!("       adventofcode2020 generate 1 <output.txt> [--count N] [--seed S]");
+    eprintln!("       adventofcode2020 audit <policy> [--input <path | ->] [--format text|json|csv]");
+    eprintln!("                        [--unit byte|char|grapheme]");
+    eprintln!("       adventofcode2020 slopes [DX/DY...] [--input <path | ->] [--search N]");
+    eprintln!();
+    eprintln!("Inputs are read from $AOC_INPUT_DIR/dayNN.txt, or input/dayNN.txt if unset.");
+    eprintln!("fetch downloads missing inputs from ${} (default {}), authenticated",
+              client::BASE_URL_VAR, client::DEFAULT_BASE_URL);
+    eprintln!("with the session token in ${} or a {} file.", client::SESSION_VAR, client::SESSION_FILE);
+    eprintln!("submit posts the computed answer and logs the attempt to ${} (default {});",
+              submit::LOG_PATH_VAR, submit::DEFAULT_LOG_PATH);
+    eprintln!("answers already rejected are never sent again. Both need curl on the PATH for https.");
+    eprintln!("new generates src/dayNN.rs, registers it in src/lib.rs and creates an empty example in");
+    eprintln!("{}/dayNN/ and an empty input.", examples::DEFAULT_DIR);
+    eprintln!("bench baselines are stored in {} unless a path is given; --compare fails when a",
+              bench::DEFAULT_BASELINE_PATH);
+    eprintln!("mean is more than --threshold percent (default {}) and {} standard deviations",
+              bench::DEFAULT_THRESHOLD, bench::NOISE_SIGMAS);
+    eprintln!("slower. A baseline named with --compare=path must exist.");
+    eprintln!("watch re-runs the example tests and the real input through cargo whenever");
+    eprintln!("src/dayNN.rs or the input file changes.");
+    eprintln!("test solves every {}/dayNN/<name>.txt and checks it against <name>.toml.",
+              examples::DEFAULT_DIR);
+    eprintln!("generate writes a random day 1 input with a unique pair and triple summing to");
+    eprintln!("2020, and its answers next to it in <output>.toml.");
+    eprintln!("audit counts the day 2 passwords satisfying a policy such as 'sled and not toboggan'");
+    eprintln!("built from sled, toboggan, and, or, not and parentheses, with a histogram of the");
+    eprintln!("failure reasons; csv and json list every violation with its line number.");
+    eprintln!("Positions and counts are in --unit (default char); a position past the end fails.");
+    eprintln!("slopes counts the day 3 trees along each slope (default the puzzle's five), where");
+    eprintln!("a negative DX moves left and 2/4 is the same slope as 1/2; --search finds the");
+    eprintln!("slopes in lowest terms with |DX| <= N and DY <= N hitting the fewest and most trees,");
+    eprintln!("in time growing with N * ln(N) times the height of the map.");
+}
+
+fn fail_usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    usage();
+    std::process::exit(1);
+}
+
+struct Options {
+    days: String,
+    format: Format,
+    record: bool,
+    input: Option<PathBuf>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut days = None;
+    let mut format = Format::Text;
+    let mut record = false;
+    let mut input = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let value = args.next().ok_or("--format expects a value")?;
+                format = value.parse()?;
+            },
+            "--record" => record = true,
+            "--input" => {
+                let value = args.next().ok_or("--input expects a path or -")?;
+                input = Some(PathBuf::from(value));
+            },
+            _ if arg.starts_with("--format=") => {
+                format = arg["--format=".len()..].parse()?;
+            },
+            _ if arg.starts_with("--input=") => {
+                input = Some(PathBuf::from(&arg["--input=".len()..]));
+            },
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if days.is_none() => days = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    Ok(Options {
+        days: days.ok_or("no day given")?,
+        format,
+        record,
+        input,
+    })
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let day = args.get(1).map(String::as_str);
-
-    let success = match day {
-        Some("01") => day01::run(&Path::new("input/day01.txt")),
-        Some("02") => day02::run(&Path::new("input/day02.txt")),
-        Some("03") => day03::run(&Path::new("input/day03.txt")),
-        Some("04") => day04::run(&Path::new("input/day04.txt")),
-        Some("05") => day05::run(&Path::new("input/day05.txt")),
-        Some("06") => day06::run(&Path::new("input/day06.txt")),
-        Some("07") => day07::run(&Path::new("input/day07.txt")),
-        _ => { eprintln!("day not found"); false }
-    };
-    
+    let args: Vec<String> = env::args().skip(1).collect();
+    let success = match args.first().map(String::as_str) {
+        Some("fetch") => fetch(&args[1..]),
+        Some("submit") => submit(&args[1..]),
+        Some("new") => new_day(&args[1..]),
+        Some("bench") => run_bench(&args[1..]),
+        Some("watch") => watch_day(&args[1..]),
+        Some("test") => test_examples(&args[1..]),
+        Some("generate") => generate(&args[1..]),
+        Some("audit") => audit(&args[1..]),
+        Some("slopes") => slopes(&args[1..]),
+        _ => run(&args),
+    };
+
     if !success { std::process::exit(1); }
 }
+
+fn fetch(args: &[String]) -> bool {
+    let numbers = match args {
+        [days] => runner::select_day_numbers(days).unwrap_or_else(|| fail_usage("day not found")),
+        _ => fail_usage("fetch expects one day selection"),
+    };
+
+    let client = Client::from_env();
+    let mut success = true;
+    for number in numbers {
+        let path = solution::input_path(number);
+        match client::fetch_cached(&client, number, &path) {
+            Ok(Fetched::Cached) => eprintln!("Day {:02}: {} already exists", number, path.display()),
+            Ok(Fetched::Downloaded) => eprintln!("Day {:02}: downloaded to {}", number, path.display()),
+            Err(e) => {
+                eprintln!("Day {:02}: could not fetch input: {}", number, e);
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+fn submit(args: &[String]) -> bool {
+    let (day, part) = match args {
+        [day, part] => (
+            day.parse().ok().and_then(find_day).unwrap_or_else(|| fail_usage("day not found")),
+            match part.as_str() {
+                "1" => 1,
+                "2" => 2,
+                _ => fail_usage("part must be 1 or 2"),
+            },
+        ),
+        _ => fail_usage("submit expects a day and a part"),
+    };
+
+    let input_path = day.input_path();
+    let input = match common::read_input(&input_path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", input_path.display(), e);
+            return false;
+        }
+    };
+    let answer = match (day.solve_part)(&input, part).map_err(|e| e.in_file(input_path.display().to_string())) {
+        Ok(answer) => answer,
+        Err(e) => {
+            eprintln!("There was a problem solving day {:02} part {}:", day.number, part);
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+
+    eprintln!("Day {:02} / Part {}: submitting {}", day.number, part, answer);
+    match submit::submit(&Client::from_env(), &submit::log_path(), day.number, part, &answer) {
+        Ok(Ok(verdict)) => {
+            let wait = verdict.wait_secs.map_or(String::new(), |s| format!(", wait {}s before the next attempt", s));
+            println!("{}{}", verdict.outcome, wait);
+            verdict.outcome == Outcome::Correct
+        },
+        Ok(Err(refusal)) => {
+            eprintln!("Not submitted: {}", refusal);
+            false
+        },
+        Err(e) => {
+            eprintln!("Could not submit: {}", e);
+            false
+        }
+    }
+}
+
+fn new_day(args: &[String]) -> bool {
+    let (day, title) = match args {
+        [day, title @ ..] => (
+            day.parse().unwrap_or_else(|_| fail_usage("day must be a number")),
+            if title.is_empty() { String::from("Untitled") } else { title.join(" ") },
+        ),
+        _ => fail_usage("new expects a day"),
+    };
+
+    match scaffold::create_in_cwd(day, &title) {
+        Ok(s) => {
+            eprintln!("Created {} and registered day {:02} in src/lib.rs", s.module.display(), day);
+            eprintln!("Fill in the example in {} and its answers next to it", s.examples.display());
+            eprintln!("Put the puzzle input in {}, or run fetch {}", s.input.display(), day);
+            true
+        },
+        Err(e) => {
+            eprintln!("Could not create day {:02}: {}", day, e);
+            false
+        }
+    }
+}
+
+struct BenchOptions {
+    days: String,
+    iterations: usize,
+    save_baseline: Option<PathBuf>,
+    compare: Option<PathBuf>,
+    /// The baseline to compare with was named, so it must exist.
+    compare_explicit: bool,
+    threshold: f64,
+}
+
+fn parse_bench_args(args: &[String]) -> Result<BenchOptions, String> {
+    let mut days = None;
+    let mut options = BenchOptions {
+        days: String::new(),
+        iterations: bench::DEFAULT_ITERATIONS,
+        save_baseline: None,
+        compare: None,
+        compare_explicit: false,
+        threshold: bench::DEFAULT_THRESHOLD,
+    };
+    let default_path = || PathBuf::from(bench::DEFAULT_BASELINE_PATH);
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => {
+                let value = args.next().ok_or("--iterations expects a number")?;
+                options.iterations = value.parse().map_err(|_| format!("invalid iteration count '{}'", value))?;
+            },
+            "--threshold" => {
+                let value = args.next().ok_or("--threshold expects a percentage")?;
+                options.threshold = value.parse().map_err(|_| format!("invalid threshold '{}'", value))?;
+            },
+            "--save-baseline" => options.save_baseline = Some(default_path()),
+            "--compare" => {
+                options.compare = Some(default_path());
+                options.compare_explicit = false;
+            },
+            _ if arg.starts_with("--save-baseline=") => {
+                options.save_baseline = Some(PathBuf::from(&arg["--save-baseline=".len()..]));
+            },
+            _ if arg.starts_with("--compare=") => {
+                options.compare = Some(PathBuf::from(&arg["--compare=".len()..]));
+                options.compare_explicit = true;
+            },
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if days.is_none() => days = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    options.days = days.ok_or("no day given")?;
+    Ok(options)
+}
+
+fn run_bench(args: &[String]) -> bool {
+    let options = parse_bench_args(args).unwrap_or_else(|e| fail_usage(&e));
+    let days = runner::select_days(&options.days).unwrap_or_else(|| fail_usage("day not found"));
+
+    let mut success = true;
+    let mut benches = Vec::new();
+    for day in days {
+        let input_path = day.input_path();
+        let input = match common::read_input(&input_path) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Could not read {}: {}", input_path.display(), e);
+                success = false;
+                continue;
+            }
+        };
+        match bench::bench_day(day, &input, options.iterations) {
+            Ok(b) => benches.push(b),
+            Err(e) => {
+                eprintln!("There was a problem solving day {:02}:", day.number);
+                eprintln!("{}", e.in_file(input_path.display().to_string()));
+                success = false;
+            }
+        }
+    }
+    print!("{}", bench::table(&benches));
+
+    if let Some(path) = &options.compare {
+        let baseline = if options.compare_explicit {
+            bench::Baseline::load(path)
+        } else {
+            bench::Baseline::load_or_default(path)
+        };
+        match baseline {
+            Ok(baseline) => {
+                let changes = bench::compare(&benches, &baseline, options.threshold);
+                println!();
+                print!("{}", bench::comparison_table(&changes));
+                if changes.iter().any(|c| c.regression) {
+                    success = false;
+                }
+            },
+            Err(e) => {
+                eprintln!("Could not load baseline {}: {}", path.display(), e);
+                success = false;
+            }
+        }
+    }
+
+    if let Some(path) = &options.save_baseline {
+        match bench::Baseline::from_benches(&benches).save(path) {
+            Ok(()) => eprintln!("Saved baseline to {}", path.display()),
+            Err(e) => { eprintln!("Could not save baseline: {}", e); success = false; }
+        }
+    }
+
+    success
+}
+
+fn watch_day(args: &[String]) -> bool {
+    let (day, interval) = match args {
+        [day] => (day.as_str(), 500),
+        [day, flag, ms] if flag == "--interval" => (
+            day.as_str(),
+            ms.parse().unwrap_or_else(|_| fail_usage("--interval expects milliseconds")),
+        ),
+        _ => fail_usage("watch expects a day"),
+    };
+    let day = day.parse().ok().and_then(find_day).unwrap_or_else(|| fail_usage("day not found"));
+
+    let mut watcher = watch::Watcher::for_day(day.number);
+    let paths: Vec<String> = watcher.paths().iter().map(|p| p.display().to_string()).collect();
+    eprintln!("Watching {}", paths.join(" and "));
+
+    let mut previous = None;
+    loop {
+        eprintln!("Day {:02}: running", day.number);
+        match watch::run_example_tests(day.number) {
+            Ok(output) if output.status.success() => println!("  Examples: pass"),
+            Ok(output) => {
+                println!("  Examples: FAIL");
+                print!("{}", String::from_utf8_lossy(&output.stdout));
+            },
+            Err(e) => eprintln!("Could not run cargo test: {}", e),
+        }
+        let answers = match watch::run_real_input(day.number) {
+            Ok(output) => {
+                if !output.status.success() {
+                    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+                }
+                watch::parse_csv_answers(&String::from_utf8_lossy(&output.stdout))
+            },
+            Err(e) => {
+                eprintln!("Could not run cargo run: {}", e);
+                None
+            }
+        };
+        print!("{}", watch::diff_answers(previous.as_ref(), answers.as_ref()));
+        if answers.is_some() {
+            previous = answers;
+        }
+
+        loop {
+            thread::sleep(Duration::from_millis(interval));
+            let changed = watcher.poll();
+            if !changed.is_empty() {
+                let names: Vec<String> = changed.iter().map(|p| p.display().to_string()).collect();
+                eprintln!();
+                eprintln!("Changed: {}", names.join(", "));
+                break;
+            }
+        }
+    }
+}
+
+fn test_examples(args: &[String]) -> bool {
+    let days = match args {
+        [days] => runner::select_days(days).unwrap_or_else(|| fail_usage("day not found")),
+        _ => fail_usage("test expects one day selection"),
+    };
+
+    let mut success = true;
+    for day in days {
+        let loaded = match examples::load(Path::new(examples::DEFAULT_DIR), day.number) {
+            Ok(e) => e,
+            Err(e) => {
+                eprintln!("Could not load the examples of day {:02}:", day.number);
+                eprintln!("{}", e);
+                success = false;
+                continue;
+            }
+        };
+        if loaded.is_empty() {
+            println!("Day {:02}: no examples", day.number);
+            continue;
+        }
+
+        println!("Day {:02}", day.number);
+        for check in examples::check(day, &loaded) {
+            let result = match &check.actual {
+                Ok(a) if check.passed() => format!("{} [pass]", a),
+                Ok(a) => format!("{} [FAIL (expected {})]", a, check.expected),
+                Err(e) => format!("error [FAIL (expected {})]\n{}", check.expected, e),
+            };
+            println!("  {} / Part {}: {}", check.example, check.part, result);
+            success &= check.passed();
+        }
+    }
+    success
+}
+
+fn generate(args: &[String]) -> bool {
+    let mut positional = Vec::new();
+    let mut count = 1000;
+    let mut seed = 0;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--count" => {
+                count = args.next().and_then(|v| v.parse().ok())
+                    .unwrap_or_else(|| fail_usage("--count expects a number"));
+            },
+            "--seed" => {
+                seed = args.next().and_then(|v| v.parse().ok())
+                    .unwrap_or_else(|| fail_usage("--seed expects a number"));
+            },
+            _ if arg.starts_with("--") => fail_usage(&format!("unknown option {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+    let output = match positional[..] {
+        [day, output] if day.parse::<u32>() == Ok(1) => PathBuf::from(output),
+        [_, _] => fail_usage("only day 1 has a generator"),
+        _ => fail_usage("generate expects a day and an output path"),
+    };
+
+    let generated = match day01::generate(seed, count, 2020) {
+        Ok(g) => g,
+        Err(e) => {
+            eprintln!("Could not generate an input: {}", e);
+            return false;
+        }
+    };
+    let answers_path = output.with_extension("toml");
+    let written = generated.answers().and_then(|answers| {
+        fs::write(&output, generated.input())?;
+        fs::write(&answers_path, format!("# seed {}, {} entries\n{}", seed, count, answers))?;
+        Ok(())
+    });
+    match written {
+        Ok(()) => {
+            eprintln!("Wrote {} and {}", output.display(), answers_path.display());
+            true
+        },
+        Err(e) => {
+            eprintln!("Could not write the generated input: {}", e);
+            false
+        }
+    }
+}
+
+fn audit(args: &[String]) -> bool {
+    let mut policy = None;
+    let mut input_path = None;
+    let mut format = Format::Text;
+    let mut unit = day02::Unit::Char;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--unit" => {
+                let value = args.next().unwrap_or_else(|| fail_usage("--unit expects byte, char or grapheme"));
+                unit = value.parse().unwrap_or_else(|e: String| fail_usage(&e));
+            },
+            _ if arg.starts_with("--unit=") => {
+                unit = arg["--unit=".len()..].parse().unwrap_or_else(|e: String| fail_usage(&e));
+            },
+            "--format" => {
+                let value = args.next().unwrap_or_else(|| fail_usage("--format expects a value"));
+                format = value.parse().unwrap_or_else(|e: String| fail_usage(&e));
+            },
+            _ if arg.starts_with("--format=") => {
+                format = arg["--format=".len()..].parse().unwrap_or_else(|e: String| fail_usage(&e));
+            },
+            "--input" => {
+                let value = args.next().unwrap_or_else(|| fail_usage("--input expects a path or -"));
+                input_path = Some(PathBuf::from(value));
+            },
+            _ if arg.starts_with("--input=") => input_path = Some(PathBuf::from(&arg["--input=".len()..])),
+            _ if arg.starts_with("--") => fail_usage(&format!("unknown option {}", arg)),
+            _ if policy.is_none() => policy = Some(arg),
+            _ => fail_usage(&format!("unexpected argument {}", arg)),
+        }
+    }
+    let policy = match day02::parse_policy(policy.unwrap_or_else(|| fail_usage("audit expects a policy")), unit) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e.in_file("<policy>"));
+            return false;
+        }
+    };
+
+    let input_path = input_path.unwrap_or_else(|| solution::input_path(2));
+    match audit_stream(&input_path, &*policy, format) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("{}", e.in_file(input_path.display().to_string()));
+            false
+        }
+    }
+}
+
+/// Audit the records of `input_path` as they are read. Only JSON keeps the
+/// failing records until the end; text and CSV need constant memory.
+fn audit_stream(input_path: &Path, policy: &dyn day02::PasswordPolicy, format: Format) -> Result<(), Error> {
+    let mut audit = day02::Audit::new(policy);
+    if format == Format::Csv {
+        print!("{}", day02::CSV_HEADER);
+    }
+    for record in day02::records(common::open_input(input_path)?) {
+        let failure = audit.tally(&record?, policy);
+        match (format, failure) {
+            (Format::Csv, Some(f)) => print!("{}", f.csv_rows()),
+            (Format::Json, Some(f)) => audit.failures.push(f),
+            _ => {},
+        }
+    }
+    if format != Format::Csv {
+        print!("{}", audit.render(format));
+    }
+    Ok(())
+}
+
+fn slopes(args: &[String]) -> bool {
+    let mut slopes = Vec::new();
+    let mut input_path = None;
+    let mut search = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let value = args.next().unwrap_or_else(|| fail_usage("--input expects a path or -"));
+                input_path = Some(PathBuf::from(value));
+            },
+            _ if arg.starts_with("--input=") => input_path = Some(PathBuf::from(&arg["--input=".len()..])),
+            "--search" => {
+                let value = args.next().unwrap_or_else(|| fail_usage("--search expects a bound"));
+                search = Some(value.parse().unwrap_or_else(|_| fail_usage("--search expects a number")));
+            },
+            _ if arg.starts_with("--") => fail_usage(&format!("unknown option {}", arg)),
+            _ => slopes.push(arg.parse::<day03::Slope>().unwrap_or_else(|e| fail_usage(&e))),
+        }
+    }
+    if search.is_some() && !slopes.is_empty() {
+        fail_usage("--search cannot be combined with slopes");
+    }
+    if slopes.is_empty() {
+        slopes = day03::PUZZLE_SLOPES.to_vec();
+    }
+
+    let input_path = input_path.unwrap_or_else(|| solution::input_path(3));
+    let map = match common::read_input(&input_path).map_err(Error::from).and_then(|input| day03::parse(&input)) {
+        Ok(map) => map,
+        Err(e) => {
+            eprintln!("{}", e.in_file(input_path.display().to_string()));
+            return false;
+        }
+    };
+
+    if let Some(bound) = search {
+        return match day03::search_slopes(&map, bound) {
+            Ok(search) => {
+                for (label, extreme) in [("Fewest", &search.fewest), ("Most", &search.most)] {
+                    let slopes: Vec<String> = extreme.slopes.iter().map(|s| s.to_string()).collect();
+                    println!("{} trees: {} along {}", label, extreme.trees, slopes.join(", "));
+                }
+                true
+            },
+            Err(e) => {
+                eprintln!("{}", e);
+                false
+            }
+        };
+    }
+
+    println!("{:>8} {:>8}", "Slope", "Trees");
+    for &slope in &slopes {
+        println!("{:>8} {:>8}", slope.to_string(), day03::count_trees(&map, slope));
+    }
+    match day03::product_of_trees(&map, &slopes) {
+        Ok(product) => {
+            println!("Product: {}", product);
+            true
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            false
+        }
+    }
+}
+
+fn run(args: &[String]) -> bool {
+    let options = parse_args(args).unwrap_or_else(|e| fail_usage(&e));
+
+    let days = runner::select_days(&options.days).unwrap_or_else(|| fail_usage("day not found"));
+    if options.input.is_some() && days.len() > 1 {
+        eprintln!("--input can only be used with a single day");
+        std::process::exit(1);
+    }
+    if options.input.is_some() && options.record {
+        eprintln!("--record cannot be used with --input");
+        std::process::exit(1);
+    }
+
+    let answers_path = Path::new(answers::DEFAULT_PATH);
+    let mut expected = match ExpectedAnswers::load(answers_path) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Could not load expected answers:");
+            eprintln!("{}", e.in_file(answers_path.display().to_string()));
+            std::process::exit(1);
+        }
+    };
+
+    let mut success = true;
+    let mut reports = Vec::new();
+    for day in days {
+        let input_path = options.input.clone().unwrap_or_else(|| day.input_path());
+        let input = match common::read_input(&input_path) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Could not read {}: {}", input_path.display(), e);
+                success = false;
+                continue;
+            }
+        };
+        let input_name = if input_path == Path::new("-") {
+            String::from("<stdin>")
+        } else {
+            input_path.display().to_string()
+        };
+        match (day.solve)(&input).map_err(|e| e.in_file(input_name)) {
+            Ok(run) => {
+                // Expected answers belong to the puzzle input, not to an override.
+                let verdicts = if options.input.is_some() {
+                    [Verdict::Unknown, Verdict::Unknown]
+                } else {
+                    expected.check_all(day.number, &run.answers)
+                };
+                if !options.record && verdicts.iter().any(|v| v.is_fail()) {
+                    success = false;
+                }
+                reports.push(Report { day: day.number, run, verdicts });
+            },
+            Err(e) => {
+                eprintln!("There was a problem solving day {:02}:", day.number);
+                eprintln!("{}", e);
+                success = false;
+            }
+        }
+    }
+
+    print!("{}", output::render(options.format, &reports));
+
+    if options.record {
+        for r in &reports {
+            expected.record(r.day, &r.run.answers);
+        }
+        match expected.save(answers_path) {
+            Ok(()) => eprintln!("Recorded answers to {}", answers_path.display()),
+            Err(e) => { eprintln!("Could not record answers: {}", e); success = false; }
+        }
+    }
+
+    success
+}
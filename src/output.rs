@@ -0,0 +1,235 @@
+use std::fmt::Write;
+use std::mem;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::answers::Verdict;
+use super::runner;
+use super::solution::{Answer, Run};
+
+/// A solved day together with the verdicts for its two answers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub day: u32,
+    pub run: Run,
+    pub verdicts: [Verdict; 2],
+}
+
+/// How the CLI reports answers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format '{}', expected json, csv or text", s)),
+        }
+    }
+}
+
+/// Render the reports of one or more days in `format`.
+pub fn render(format: Format, reports: &[Report]) -> String {
+    match format {
+        Format::Text => render_text(reports),
+        Format::Json => render_json(reports),
+        Format::Csv => render_csv(reports),
+    }
+}
+
+fn render_text(reports: &[Report]) -> String {
+    let mut out = String::new();
+    if reports.is_empty() {
+        return out;
+    }
+    for r in reports {
+        writeln!(out, "Day {:02}", r.day).unwrap();
+        writeln!(out, "  Part 1: {} [{}]", r.run.answers.part1, r.verdicts[0]).unwrap();
+        writeln!(out, "  Part 2: {} [{}]", r.run.answers.part2, r.verdicts[1]).unwrap();
+    }
+
+    let timings: Vec<_> = reports.iter().map(|r| (r.day, r.run.timings)).collect();
+    writeln!(out).unwrap();
+    out.push_str(&runner::timing_table(&timings));
+    out
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Quote `s` as a JSON string.
+pub fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn json_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Number(n) => n.to_string(),
+        Answer::Text(s) => json_string(s),
+    }
+}
+
+fn verdict_name(verdict: &Verdict) -> &'static str {
+    match verdict {
+        Verdict::Pass => "pass",
+        Verdict::Fail { .. } => "fail",
+        Verdict::Unknown => "unknown",
+    }
+}
+
+fn render_json(reports: &[Report]) -> String {
+    let days: Vec<String> = reports.iter()
+        .map(|r| {
+            format!("    {{\"day\": {}, \"part1\": {}, \"part2\": {}, \
+                     \"part1_status\": \"{}\", \"part2_status\": \"{}\", \
+                     \"parse_ms\": {:.3}, \"part1_ms\": {:.3}, \"part2_ms\": {:.3}}}",
+                    r.day,
+                    json_answer(&r.run.answers.part1),
+                    json_answer(&r.run.answers.part2),
+                    verdict_name(&r.verdicts[0]),
+                    verdict_name(&r.verdicts[1]),
+                    millis(r.run.timings.parse),
+                    millis(r.run.timings.part1),
+                    millis(r.run.timings.part2))
+        })
+        .collect();
+    format!("{{\n  \"days\": [\n{}\n  ]\n}}\n", days.join(",\n"))
+}
+
+/// Quote `s` as a CSV field if it needs to be.
+pub fn csv_string(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Split CSV text into records of fields, undoing the quoting of
+/// [`csv_string`]: a quoted field may hold commas, newlines and doubled
+/// quotes. `None` if a quoted field is not closed.
+pub fn parse_csv(text: &str) -> Option<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(mem::take(&mut field)),
+            '\n' if !quoted => {
+                record.push(mem::take(&mut field));
+                records.push(mem::take(&mut record));
+            },
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Some(records)
+}
+
+fn csv_field(answer: &Answer) -> String {
+    csv_string(&answer.to_string())
+}
+
+fn render_csv(reports: &[Report]) -> String {
+    let mut out = String::from("day,part1,part2,part1_status,part2_status,parse_ms,part1_ms,part2_ms\n");
+    for r in reports {
+        writeln!(out, "{},{},{},{},{},{:.3},{:.3},{:.3}",
+                 r.day,
+                 csv_field(&r.run.answers.part1),
+                 csv_field(&r.run.answers.part2),
+                 verdict_name(&r.verdicts[0]),
+                 verdict_name(&r.verdicts[1]),
+                 millis(r.run.timings.parse),
+                 millis(r.run.timings.part1),
+                 millis(r.run.timings.part2)).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{Answers, Timings};
+
+    fn example_reports() -> Vec<Report> {
+        vec![Report {
+            day: 1,
+            run: Run {
+                answers: Answers {
+                    part1: Answer::Number(42),
+                    part2: Answer::Text(String::from("a,\"b\"")),
+                },
+                timings: Timings::default(),
+            },
+            verdicts: [Verdict::Pass, Verdict::Unknown],
+        }]
+    }
+
+    #[test]
+    fn json() {
+        let json = render(Format::Json, &example_reports());
+        assert!(json.contains("\"day\": 1, \"part1\": 42, \"part2\": \"a,\\\"b\\\"\", \
+                               \"part1_status\": \"pass\", \"part2_status\": \"unknown\""));
+    }
+
+    #[test]
+    fn csv() {
+        let csv = render(Format::Csv, &example_reports());
+        assert_eq!(csv.lines().nth(1), Some("1,42,\"a,\"\"b\"\"\",pass,unknown,0.000,0.000,0.000"));
+    }
+
+    #[test]
+    fn csv_reads_back() {
+        let fields = ["plain", "a,b", "say \"hi\"", "two\nlines", "", "\"", "cr\r"];
+        let line: Vec<String> = fields.iter().map(|f| csv_string(f)).collect();
+        let text = format!("h1,h2\n{}\nlast", line.join(","));
+        assert_eq!(parse_csv(&text).unwrap(), vec![
+            vec![String::from("h1"), String::from("h2")],
+            fields.iter().map(|f| f.to_string()).collect(),
+            vec![String::from("last")],
+        ]);
+        assert_eq!(parse_csv("").unwrap(), Vec::<Vec<String>>::new());
+        assert_eq!(parse_csv("a,\"b\n"), None);
+    }
+
+    #[test]
+    fn format_names() {
+        assert_eq!("json".parse(), Ok(Format::Json));
+        assert_eq!("csv".parse(), Ok(Format::Csv));
+        assert_eq!("text".parse(), Ok(Format::Text));
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
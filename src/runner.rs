@@ -0,0 +1,114 @@
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use super::solution::{Day, Timings};
+use super::DAYS;
+
+/// The last day of the puzzle calendar.
+pub const LAST_DAY: u32 = 25;
+
+/// Parse `all`, a single day (`5` or `05`) or an inclusive range (`01-05`)
+/// into the first and last day selected.
+pub fn parse_day_range(arg: &str) -> Option<RangeInclusive<u32>> {
+    if arg == "all" {
+        Some(1..=LAST_DAY)
+    } else if let Some((first, last)) = arg.split_once('-') {
+        Some(first.parse().ok()?..=last.parse().ok()?)
+    } else {
+        let n = arg.parse().ok()?;
+        Some(n..=n)
+    }
+}
+
+/// Select registered days from a command line argument, see
+/// [`parse_day_range`]. Returns `None` if the argument is malformed or
+/// selects no registered day.
+pub fn select_days(arg: &str) -> Option<Vec<&'static Day>> {
+    let range = parse_day_range(arg)?;
+    let days: Vec<&Day> = DAYS.iter()
+        .filter(|d| range.contains(&d.number))
+        .collect();
+    if days.is_empty() { None } else { Some(days) }
+}
+
+/// Select day numbers from a command line argument, whether or not they are
+/// registered. `all` selects only the registered days.
+pub fn select_day_numbers(arg: &str) -> Option<Vec<u32>> {
+    if arg == "all" {
+        return Some(DAYS.iter().map(|d| d.number).collect());
+    }
+    let numbers: Vec<u32> = parse_day_range(arg)?
+        .filter(|n| (1..=LAST_DAY).contains(n))
+        .collect();
+    if numbers.is_empty() { None } else { Some(numbers) }
+}
+
+/// Format a duration in milliseconds with microsecond precision.
+pub fn format_duration(d: Duration) -> String {
+    format!("{:.3} ms", d.as_secs_f64() * 1000.0)
+}
+
+/// Render a table of per-day timings with a totals row.
+pub fn timing_table(rows: &[(u32, Timings)]) -> String {
+    let mut table = String::new();
+    let mut write_row = |label: &str, t: &Timings| {
+        writeln!(table, "{:<6} {:>12} {:>12} {:>12} {:>12}",
+                 label,
+                 format_duration(t.parse),
+                 format_duration(t.part1),
+                 format_duration(t.part2),
+                 format_duration(t.total())).unwrap();
+    };
+
+    let mut totals = Timings::default();
+    for (number, t) in rows {
+        write_row(&format!("{:02}", number), t);
+        totals.parse += t.parse;
+        totals.part1 += t.part1;
+        totals.part2 += t.part2;
+    }
+    write_row("Total", &totals);
+
+    let header = format!("{:<6} {:>12} {:>12} {:>12} {:>12}\n",
+                         "Day", "Parse", "Part 1", "Part 2", "Total");
+    let rule = "-".repeat(header.len() - 1);
+    format!("{}{}\n{}", header, rule, table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(days: Option<Vec<&Day>>) -> Option<Vec<u32>> {
+        days.map(|v| v.iter().map(|d| d.number).collect())
+    }
+
+    #[test]
+    fn selection() {
+        assert_eq!(numbers(select_days("03")), Some(vec![3]));
+        assert_eq!(numbers(select_days("3")), Some(vec![3]));
+        assert_eq!(numbers(select_days("02-04")), Some(vec![2, 3, 4]));
+        assert_eq!(numbers(select_days("all")).map(|v| v.len()), Some(DAYS.len()));
+        assert_eq!(numbers(select_days("99")), None);
+        assert_eq!(numbers(select_days("05-01")), None);
+        assert_eq!(numbers(select_days("x")), None);
+
+        assert_eq!(select_day_numbers("24-30"), Some(vec![24, 25]));
+        assert_eq!(select_day_numbers("0"), None);
+        assert_eq!(select_day_numbers("all").map(|v| v.len()), Some(DAYS.len()));
+    }
+
+    #[test]
+    fn table_totals() {
+        let t = Timings {
+            parse: Duration::from_millis(1),
+            part1: Duration::from_millis(2),
+            part2: Duration::from_millis(3),
+        };
+        let table = timing_table(&[(1, t), (2, t)]);
+        let last = table.lines().last().unwrap();
+        assert!(last.starts_with("Total"));
+        assert!(last.ends_with("12.000 ms"));
+    }
+}
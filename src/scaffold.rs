@@ -0,0 +1,213 @@
+//! Generating the module, registration, example files and input file for a
+//! new day.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::error::{Error, Result};
+use super::examples;
+use super::runner::LAST_DAY;
+use super::solution;
+
+const TEMPLATE: &str = r#"use super::error::{Error, Result};
+use super::solution::{Answer, Solution};
+
+/// Day {day}: {title}.
+pub struct Day{NN};
+impl Solution for Day{NN} {
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(_input: &Self::Input) -> Result<Answer> {
+        Err(Error::MissingAnswer(String::from("part 1 is not solved yet")))
+    }
+
+    fn part2(_input: &Self::Input) -> Result<Answer> {
+        Err(Error::MissingAnswer(String::from("part 2 is not solved yet")))
+    }
+}
+
+/// Parse the puzzle input.
+pub fn parse(input: &str) -> Result<Vec<String>> {
+    Ok(input.lines().map(String::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const EXAMPLE_INPUT: &str = "\
+";
+
+    #[test]
+    #[ignore = "fill in the example input and answer"]
+    fn example_part1() {
+        let input = parse(EXAMPLE_INPUT).unwrap();
+        assert_eq!(Day{NN}::part1(&input).unwrap(), Answer::Number(0));
+    }
+
+    #[test]
+    #[ignore = "fill in the example input and answer"]
+    fn example_part2() {
+        let input = parse(EXAMPLE_INPUT).unwrap();
+        assert_eq!(Day{NN}::part2(&input).unwrap(), Answer::Number(0));
+    }
+}
+"#;
+
+/// The sidecar of the generated example, with no answers to check yet.
+const EXAMPLE_SIDECAR: &str = "\
+# Expected answers for example.txt, checked by `cargo test --test examples`
+# and the test subcommand once uncommented.
+# part1 = 0
+# part2 = 0
+";
+
+/// The source of a new, unsolved day module.
+pub fn module_source(day: u32, title: &str) -> String {
+    TEMPLATE
+        .replace("{day}", &day.to_string())
+        .replace("{NN}", &format!("{:02}", day))
+        .replace("{title}", title)
+}
+
+/// Add `day` to the module list and the `DAYS` registry of the library
+/// source `lib`, keeping both in order.
+pub fn register(lib: &str, day: u32) -> Result<String> {
+    let module = format!("pub mod day{:02};", day);
+    let entry = format!("    Day {{ number: {0}, solve: solve::<day{0:02}::Day{0:02}>, solve_part: solve_part::<day{0:02}::Day{0:02}> }},", day);
+    if lib.lines().any(|l| l == module) {
+        return Err(Error::Validation(format!("day {:02} is already registered", day)));
+    }
+
+    let mut lines: Vec<&str> = lib.lines().collect();
+    let module_at = insert_position(&lines, &module, |l| l.starts_with("pub mod day"))
+        .ok_or_else(|| Error::Validation(String::from("could not find the day module list")))?;
+    lines.insert(module_at, &module);
+    let entry_at = insert_position(&lines, &entry, |l| l.trim_start().starts_with("Day { number: "))
+        .ok_or_else(|| Error::Validation(String::from("could not find the DAYS registry")))?;
+    lines.insert(entry_at, &entry);
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Where `new` goes in the block of lines matching `is_item`, ordered by the
+/// first number in each line.
+fn insert_position(lines: &[&str], new: &str, is_item: impl Fn(&str) -> bool) -> Option<usize> {
+    let first = lines.iter().position(|l| is_item(l))?;
+    let len = lines[first..].iter().take_while(|l| is_item(l)).count();
+    let key = |l: &str| l.split(|c: char| !c.is_ascii_digit())
+        .find(|s| !s.is_empty())
+        .and_then(|s| s.parse::<u32>().ok());
+    let offset = lines[first..first + len].iter()
+        .position(|l| key(l) > key(new))
+        .unwrap_or(len);
+    Some(first + offset)
+}
+
+/// The files written for a new day.
+#[derive(Debug)]
+pub struct Scaffold {
+    pub module: PathBuf,
+    /// The directory holding `example.txt` and `example.toml`.
+    pub examples: PathBuf,
+    pub input: PathBuf,
+}
+
+/// Write `contents` to `path` unless it already exists.
+fn create_file(path: &Path, contents: &str) -> Result<()> {
+    if !path.exists() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+/// Generate `src/dayNN.rs` under `root`, register it in `src/lib.rs`, add
+/// an empty example with its sidecar to `examples/dayNN/` and create an
+/// empty input file at `input`. Nothing is overwritten.
+pub fn create(root: &Path, day: u32, title: &str, input: &Path) -> Result<Scaffold> {
+    if !(1..=LAST_DAY).contains(&day) {
+        return Err(Error::Validation(format!("day must be between 1 and {}", LAST_DAY)));
+    }
+    let module = root.join("src").join(format!("day{:02}.rs", day));
+    if module.exists() {
+        return Err(Error::Validation(format!("{} already exists", module.display())));
+    }
+    let lib_path = root.join("src").join("lib.rs");
+    let lib = register(&fs::read_to_string(&lib_path)?, day)?;
+
+    fs::write(&module, module_source(day, title))?;
+    fs::write(&lib_path, lib)?;
+    let examples = examples::day_dir(&root.join(examples::DEFAULT_DIR), day);
+    create_file(&examples.join("example.txt"), "")?;
+    create_file(&examples.join("example.toml"), EXAMPLE_SIDECAR)?;
+    create_file(input, "")?;
+    Ok(Scaffold { module, examples, input: input.to_path_buf() })
+}
+
+/// See [`create`], with the input file in the usual input directory.
+pub fn create_in_cwd(day: u32, title: &str) -> Result<Scaffold> {
+    create(Path::new("."), day, title, &solution::input_path(day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "\
+pub mod solution;
+pub mod day01;
+pub mod day03;
+
+pub const DAYS: &[Day] = &[
+    Day { number: 1, solve: solve::<day01::Day01>, solve_part: solve_part::<day01::Day01> },
+    Day { number: 3, solve: solve::<day03::Day03>, solve_part: solve_part::<day03::Day03> },
+];
+";
+
+    #[test]
+    fn registration_is_ordered() {
+        let lib = register(LIB, 2).unwrap();
+        assert!(lib.contains("pub mod day01;\npub mod day02;\npub mod day03;\n"));
+        assert!(lib.contains("Day01> },\n    Day { number: 2, solve: solve::<day02::Day02>, solve_part: solve_part::<day02::Day02> },\n    Day { number: 3"));
+
+        let lib = register(LIB, 12).unwrap();
+        assert!(lib.contains("pub mod day03;\npub mod day12;\n\n"));
+        assert!(lib.contains("Day { number: 12, solve: solve::<day12::Day12>, solve_part: solve_part::<day12::Day12> },\n];"));
+
+        assert!(register(LIB, 3).is_err());
+        assert!(register("pub mod solution;\n", 2).is_err());
+    }
+
+    #[test]
+    fn template() {
+        let source = module_source(8, "Handheld Halting");
+        assert!(source.contains("/// Day 8: Handheld Halting.\npub struct Day08;"));
+        assert!(source.contains("impl Solution for Day08 {"));
+        assert!(source.contains("Day08::part2(&input)"));
+        assert!(!source.contains("{NN}") && !source.contains("{day}"));
+        assert_eq!(examples::parse_expected(EXAMPLE_SIDECAR).unwrap(), [None, None]);
+    }
+
+    #[test]
+    fn create_writes_every_file() {
+        let root = std::env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("lib.rs"), LIB).unwrap();
+
+        let scaffold = create(&root, 8, "Handheld Halting", &root.join("input").join("day08.txt")).unwrap();
+        assert_eq!(scaffold.examples, root.join("examples").join("day08"));
+        let loaded = examples::load(&root.join("examples"), 8).unwrap();
+        assert_eq!((loaded.len(), loaded[0].name.as_str(), loaded[0].expected.clone()), (1, "example", [None, None]));
+        assert!(fs::read_to_string(root.join("src").join("lib.rs")).unwrap().contains("pub mod day08;"));
+        assert!(scaffold.module.exists() && scaffold.input.exists());
+        assert!(create(&root, 8, "Again", &scaffold.input).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
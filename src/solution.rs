@@ -0,0 +1,131 @@
+use std::fmt;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::error::{Error, Result};
+
+/// The answer to one part of a puzzle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    Number(u64),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self { Answer::Number(n as u64) }
+}
+impl From<u32> for Answer {
+    fn from(n: u32) -> Self { Answer::Number(n as u64) }
+}
+impl From<u64> for Answer {
+    fn from(n: u64) -> Self { Answer::Number(n) }
+}
+impl From<String> for Answer {
+    fn from(s: String) -> Self { Answer::Text(s) }
+}
+
+/// A puzzle solution: parse the input once, then solve both parts from it.
+pub trait Solution {
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Answer>;
+    fn part2(input: &Self::Input) -> Result<Answer>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answers {
+    pub part1: Answer,
+    pub part2: Answer,
+}
+
+/// Wall time spent in each stage of a solution.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.part1 + self.part2
+    }
+}
+
+/// The answers of a solved day and how long each stage took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub answers: Answers,
+    pub timings: Timings,
+}
+
+/// Parse `input` and solve both parts with solution `S`, timing each stage.
+pub fn solve<S: Solution>(input: &str) -> Result<Run> {
+    let start = Instant::now();
+    let parsed = S::parse(input)?;
+    let parse = start.elapsed();
+
+    let start = Instant::now();
+    let part1 = S::part1(&parsed)?;
+    let part1_time = start.elapsed();
+
+    let start = Instant::now();
+    let part2 = S::part2(&parsed)?;
+    let part2_time = start.elapsed();
+
+    Ok(Run {
+        answers: Answers { part1, part2 },
+        timings: Timings { parse, part1: part1_time, part2: part2_time },
+    })
+}
+
+/// Parse `input` and solve only `part` (1 or 2) with solution `S`.
+pub fn solve_part<S: Solution>(input: &str, part: u8) -> Result<Answer> {
+    let parsed = S::parse(input)?;
+    match part {
+        1 => S::part1(&parsed),
+        2 => S::part2(&parsed),
+        _ => Err(Error::Validation(format!("there is no part {}", part))),
+    }
+}
+
+/// Environment variable overriding the directory puzzle inputs are read from.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// An entry in the day registry.
+pub struct Day {
+    pub number: u32,
+    pub solve: fn(&str) -> Result<Run>,
+    pub solve_part: fn(&str, u8) -> Result<Answer>,
+}
+impl Day {
+    /// See [`input_path`].
+    pub fn input_path(&self) -> PathBuf {
+        input_path(self.number)
+    }
+
+    pub fn input_path_in(&self, dir: &Path) -> PathBuf {
+        input_path_in(dir, self.number)
+    }
+}
+
+/// The input file of `day` inside the directory named by [`INPUT_DIR_VAR`],
+/// or `input/` if it is not set.
+pub fn input_path(day: u32) -> PathBuf {
+    let dir = env::var_os(INPUT_DIR_VAR).unwrap_or_else(|| "input".into());
+    input_path_in(Path::new(&dir), day)
+}
+
+pub fn input_path_in(dir: &Path, day: u32) -> PathBuf {
+    dir.join(format!("day{:02}.txt", day))
+}
@@ -0,0 +1,385 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::client::Client;
+use super::error::{Error, Result};
+use super::solution::Answer;
+
+/// Default location of the submission log, relative to the working directory.
+pub const DEFAULT_LOG_PATH: &str = "submissions.log";
+/// Environment variable overriding the location of the submission log.
+pub const LOG_PATH_VAR: &str = "AOC_SUBMISSION_LOG";
+
+/// The submission log named by [`LOG_PATH_VAR`], or [`DEFAULT_LOG_PATH`].
+pub fn log_path() -> PathBuf {
+    env::var_os(LOG_PATH_VAR).map_or_else(|| PathBuf::from(DEFAULT_LOG_PATH), PathBuf::from)
+}
+
+/// What the website said about a submitted answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint in which direction.
+    Incorrect,
+    /// Submitted too soon after the previous attempt; nothing was checked.
+    Wait,
+    /// The part has already been solved.
+    AlreadyCompleted,
+    /// A response that could not be interpreted.
+    Unknown,
+}
+
+impl Outcome {
+    fn name(&self) -> &'static str {
+        match self {
+            Outcome::Correct => "correct",
+            Outcome::TooHigh => "too-high",
+            Outcome::TooLow => "too-low",
+            Outcome::Incorrect => "incorrect",
+            Outcome::Wait => "wait",
+            Outcome::AlreadyCompleted => "already-completed",
+            Outcome::Unknown => "unknown",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Outcome> {
+        [Outcome::Correct, Outcome::TooHigh, Outcome::TooLow, Outcome::Incorrect,
+         Outcome::Wait, Outcome::AlreadyCompleted, Outcome::Unknown]
+            .iter()
+            .find(|o| o.name() == name)
+            .cloned()
+    }
+
+    /// The answer was checked and found wrong.
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Outcome::TooHigh | Outcome::TooLow | Outcome::Incorrect)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The interpreted response to a submission.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    pub outcome: Outcome,
+    /// Seconds to wait before the next submission, if the site asked for it.
+    pub wait_secs: Option<u64>,
+}
+
+/// Interpret the HTML page returned for a submitted answer.
+pub fn parse_response(body: &str) -> Verdict {
+    let outcome = if body.contains("That's the right answer") {
+        Outcome::Correct
+    } else if body.contains("You gave an answer too recently") {
+        Outcome::Wait
+    } else if body.contains("Did you already complete it") {
+        Outcome::AlreadyCompleted
+    } else if body.contains("That's not the right answer") {
+        if body.contains("your answer is too high") {
+            Outcome::TooHigh
+        } else if body.contains("your answer is too low") {
+            Outcome::TooLow
+        } else {
+            Outcome::Incorrect
+        }
+    } else {
+        Outcome::Unknown
+    };
+    Verdict { outcome, wait_secs: parse_wait(body) }
+}
+
+/// Find the waiting time in phrases like "you have 1m 5s left to wait" or
+/// "please wait 5 minutes before trying again". The body comes from the
+/// server, so a time that does not fit in a `u64` is no time at all.
+fn parse_wait(body: &str) -> Option<u64> {
+    let body = body.to_ascii_lowercase();
+    if let Some(end) = body.find(" left to wait") {
+        let start = body[..end].rfind("have ").map_or(0, |i| i + "have ".len());
+        let mut secs: u64 = 0;
+        for part in body[start..end].split_whitespace() {
+            let (n, unit) = part.split_at(part.find(|c: char| !c.is_ascii_digit())?);
+            let n: u64 = n.parse().ok()?;
+            let part_secs = match unit {
+                "h" => n.checked_mul(3600)?,
+                "m" => n.checked_mul(60)?,
+                "s" => n,
+                _ => return None,
+            };
+            secs = secs.checked_add(part_secs)?;
+        }
+        return Some(secs);
+    }
+    if let Some(start) = body.find("please wait ") {
+        let rest = &body[start + "please wait ".len()..];
+        let mut words = rest.split_whitespace();
+        let n: u64 = match words.next()? {
+            "one" => 1,
+            "two" => 2,
+            "five" => 5,
+            w => w.parse().ok()?,
+        };
+        let unit = words.next()?;
+        return if unit.starts_with("minute") { n.checked_mul(60) } else if unit.starts_with("second") { Some(n) } else { None };
+    }
+    None
+}
+
+/// One line of the submission log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub day: u32,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl Attempt {
+    fn to_line(&self) -> String {
+        format!("{}\t{}\t{}\t{}\t{}\t{}\n",
+                self.time, self.day, self.part, self.verdict.outcome,
+                self.verdict.wait_secs.map_or(String::from("-"), |s| s.to_string()),
+                self.answer)
+    }
+
+    fn from_line(line: &str) -> Option<Attempt> {
+        let mut fields = line.splitn(6, '\t');
+        Some(Attempt {
+            time: fields.next()?.parse().ok()?,
+            day: fields.next()?.parse().ok()?,
+            part: fields.next()?.parse().ok()?,
+            verdict: Verdict {
+                outcome: Outcome::from_name(fields.next()?)?,
+                wait_secs: match fields.next()? {
+                    "-" => None,
+                    s => Some(s.parse().ok()?),
+                },
+            },
+            answer: fields.next()?.trim_end_matches('\r').to_string(),
+        })
+    }
+}
+
+/// Why an answer was not sent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Refusal {
+    AlreadySubmitted(Attempt),
+    AlreadySolved(Attempt),
+    /// The site asked to wait; this many seconds remain.
+    RateLimited(u64),
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::AlreadySubmitted(a) =>
+                write!(f, "answer {} was already submitted and was {}", a.answer, a.verdict.outcome),
+            Refusal::AlreadySolved(a) =>
+                write!(f, "day {:02} part {} was already solved with {}", a.day, a.part, a.answer),
+            Refusal::RateLimited(secs) =>
+                write!(f, "submitting too soon, wait another {}s", secs),
+        }
+    }
+}
+
+/// Every submission attempt made, stored as tab separated lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubmissionLog {
+    pub attempts: Vec<Attempt>,
+}
+
+impl SubmissionLog {
+    /// Load the log at `path`. A missing file is an empty log; malformed lines
+    /// are skipped.
+    pub fn load(path: &Path) -> Result<SubmissionLog> {
+        let contents = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(Error::Io(e)),
+        };
+        Ok(SubmissionLog::parse(&contents))
+    }
+
+    /// Parse the lines of a log, skipping malformed ones.
+    pub fn parse(contents: &str) -> SubmissionLog {
+        SubmissionLog { attempts: contents.lines().filter_map(Attempt::from_line).collect() }
+    }
+
+    pub fn to_text(&self) -> String {
+        self.attempts.iter().map(Attempt::to_line).collect()
+    }
+
+    /// Append `attempt` to the log at `path` and to `self`.
+    pub fn append(&mut self, path: &Path, attempt: Attempt) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(attempt.to_line().as_bytes())?;
+        self.attempts.push(attempt);
+        Ok(())
+    }
+
+    /// Check whether `answer` may be submitted for `day` and `part` at `now`.
+    pub fn check(&self, day: u32, part: u8, answer: &str, now: u64) -> std::result::Result<(), Refusal> {
+        let for_part = || self.attempts.iter().filter(|a| a.day == day && a.part == part);
+        if let Some(a) = for_part().find(|a| a.verdict.outcome == Outcome::Correct) {
+            return Err(Refusal::AlreadySolved(a.clone()));
+        }
+        if let Some(a) = for_part().find(|a| a.answer == answer && a.verdict.outcome.is_wrong()) {
+            return Err(Refusal::AlreadySubmitted(a.clone()));
+        }
+        // The rate limit applies across all puzzles. A wait too long to add
+        // up lasts until the end of time.
+        let wait_until = self.attempts.iter()
+            .filter_map(|a| a.verdict.wait_secs.map(|w| a.time.saturating_add(w)))
+            .max();
+        match wait_until {
+            Some(t) if t > now => Err(Refusal::RateLimited(t - now)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+impl Client {
+    /// Post `answer` for `part` of `day` and interpret the response.
+    pub fn submit_answer(&self, day: u32, part: u8, answer: &Answer) -> Result<Verdict> {
+        let body = format!("level={}&answer={}", part, url_encode(&answer.to_string()));
+        let response = self.request("POST", &format!("{}/answer", self.day_url(day)), Some(&body))?;
+        Ok(parse_response(&response))
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Submit `answer` unless the log says it is pointless or too soon, and log
+/// the attempt.
+pub fn submit(client: &Client, log_path: &Path, day: u32, part: u8, answer: &Answer)
+    -> Result<std::result::Result<Verdict, Refusal>> {
+    let mut log = SubmissionLog::load(log_path)?;
+    let answer_str = answer.to_string();
+    let time = now();
+    if let Err(refusal) = log.check(day, part, &answer_str, time) {
+        return Ok(Err(refusal));
+    }
+
+    let verdict = client.submit_answer(day, part, answer)?;
+    log.append(log_path, Attempt { time, day, part, answer: answer_str, verdict: verdict.clone() })?;
+    Ok(Ok(verdict))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock;
+
+    fn verdict(outcome: Outcome, wait_secs: Option<u64>) -> Verdict {
+        Verdict { outcome, wait_secs }
+    }
+
+    #[test]
+    fn responses() {
+        assert_eq!(parse_response("<p>That's the right answer! You are one gold star closer.</p>"),
+                   verdict(Outcome::Correct, None));
+        assert_eq!(parse_response("That's not the right answer; your answer is too high. \
+                                   Please wait one minute before trying again."),
+                   verdict(Outcome::TooHigh, Some(60)));
+        assert_eq!(parse_response("That's not the right answer; your answer is too low. \
+                                   please wait 5 minutes before trying again."),
+                   verdict(Outcome::TooLow, Some(300)));
+        assert_eq!(parse_response("That's not the right answer. If you're stuck..."),
+                   verdict(Outcome::Incorrect, None));
+        assert_eq!(parse_response("You gave an answer too recently; you have 1m 5s left to wait."),
+                   verdict(Outcome::Wait, Some(65)));
+        assert_eq!(parse_response("You don't seem to be solving the right level. Did you already complete it?"),
+                   verdict(Outcome::AlreadyCompleted, None));
+        assert_eq!(parse_response("<html></html>"), verdict(Outcome::Unknown, None));
+    }
+
+    #[test]
+    fn wait_overflow() {
+        assert_eq!(parse_wait("you have 18446744073709551615s left to wait"), Some(u64::MAX));
+        assert_eq!(parse_wait("you have 18446744073709551616s left to wait"), None);
+        assert_eq!(parse_wait("you have 5124095576030431h 15s left to wait"), Some(u64::MAX));
+        assert_eq!(parse_wait("you have 5124095576030431h 16s left to wait"), None);
+        assert_eq!(parse_wait("you have 5124095576030432h left to wait"), None);
+        assert_eq!(parse_wait("you have 307445734561825860m 15s left to wait"), Some(u64::MAX));
+        assert_eq!(parse_wait("you have 307445734561825861m left to wait"), None);
+        assert_eq!(parse_wait("please wait 307445734561825860 minutes"), Some(u64::MAX - 15));
+        assert_eq!(parse_wait("please wait 307445734561825861 minutes"), None);
+    }
+
+    #[test]
+    fn log_round_trip_and_checks() {
+        let attempt = |time, part, answer: &str, outcome, wait| Attempt {
+            time, day: 1, part, answer: answer.to_string(), verdict: verdict(outcome, wait),
+        };
+        let log = SubmissionLog { attempts: vec![
+            attempt(100, 1, "12", Outcome::TooLow, Some(60)),
+            attempt(200, 2, "42", Outcome::Correct, None),
+        ] };
+        for a in &log.attempts {
+            assert_eq!(Attempt::from_line(a.to_line().trim_end()).as_ref(), Some(a));
+        }
+
+        assert_eq!(log.check(1, 1, "12", 1000), Err(Refusal::AlreadySubmitted(log.attempts[0].clone())));
+        assert_eq!(log.check(1, 2, "43", 1000), Err(Refusal::AlreadySolved(log.attempts[1].clone())));
+        assert_eq!(log.check(1, 1, "13", 130), Err(Refusal::RateLimited(30)));
+        assert_eq!(log.check(1, 1, "13", 160), Ok(()));
+    }
+
+    #[test]
+    fn huge_waits_do_not_overflow() {
+        let log = SubmissionLog::parse("100\t1\t1\twait\t18446744073709551615\t42\n");
+        assert_eq!(log.attempts.len(), 1);
+        assert_eq!(log.check(1, 1, "43", 1000), Err(Refusal::RateLimited(u64::MAX - 1000)));
+    }
+
+    #[test]
+    fn submit_against_mock() {
+        let (base_url, server) = mock::serve(vec![
+            (200, String::from("<article><p>That's not the right answer; your answer is too low.</p></article>")),
+        ]);
+        let client = Client::new(&base_url, Some(String::from("abc")));
+        let log_path = env::temp_dir().join(format!("aoc2020-submissions-{}.log", std::process::id()));
+        let _ = fs::remove_file(&log_path);
+
+        let answer = Answer::Number(12);
+        let result = submit(&client, &log_path, 1, 1, &answer).unwrap();
+        assert_eq!(result, Ok(verdict(Outcome::TooLow, None)));
+        // The same wrong answer is refused without contacting the server.
+        assert!(matches!(submit(&client, &log_path, 1, 1, &answer).unwrap(),
+                         Err(Refusal::AlreadySubmitted(_))));
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /2020/day/1/answer HTTP/1.1\r\n"));
+        assert!(requests[0].ends_with("\r\n\r\nlevel=1&answer=12"));
+        assert_eq!(SubmissionLog::load(&log_path).unwrap().attempts.len(), 1);
+        fs::remove_file(&log_path).unwrap();
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(url_encode("a b&c"), "a%20b%26c");
+    }
+}
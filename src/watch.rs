@@ -0,0 +1,151 @@
+//! Re-running a day whenever its source or input changes.
+//!
+//! The running binary cannot pick up edits to its own source, so every run
+//! goes through `cargo`, which rebuilds as needed.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::SystemTime;
+
+use super::output;
+use super::solution;
+
+/// Polls the modification times of a set of files.
+#[derive(Debug)]
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    mtimes: Vec<Option<SystemTime>>,
+}
+
+impl Watcher {
+    pub fn new(paths: Vec<PathBuf>) -> Watcher {
+        let mtimes = paths.iter().map(|p| mtime(p)).collect();
+        Watcher { paths, mtimes }
+    }
+
+    /// Watch the source and input files of `day`.
+    pub fn for_day(day: u32) -> Watcher {
+        Watcher::new(vec![
+            Path::new("src").join(format!("day{:02}.rs", day)),
+            solution::input_path(day),
+        ])
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// The files that were modified, created or removed since the last poll.
+    pub fn poll(&mut self) -> Vec<&Path> {
+        let mut changed = Vec::new();
+        for (path, last) in self.paths.iter().zip(self.mtimes.iter_mut()) {
+            let current = mtime(path);
+            if current != *last {
+                *last = current;
+                changed.push(path.as_path());
+            }
+        }
+        changed
+    }
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn cargo() -> Command {
+    Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+}
+
+/// Run the unit tests of `day`, then its data-file examples in
+/// `tests/examples.rs`. The output of both runs is concatenated, and the
+/// status is that of the first to fail.
+pub fn run_example_tests(day: u32) -> std::io::Result<Output> {
+    let name = format!("day{:02}", day);
+    let mut output = cargo().args(["test", "--quiet", "--lib", &format!("{}::", name)]).output()?;
+    let examples = cargo().args(["test", "--quiet", "--test", "examples", "--", &name]).output()?;
+    output.stdout.extend(examples.stdout);
+    output.stderr.extend(examples.stderr);
+    if output.status.success() {
+        output.status = examples.status;
+    }
+    Ok(output)
+}
+
+/// Solve `day` on its real input and return the CSV output.
+pub fn run_real_input(day: u32) -> std::io::Result<Output> {
+    cargo().args(["run", "--quiet", "--", &day.to_string(), "--format", "csv"]).output()
+}
+
+/// The two answers from the CSV output of a single day.
+pub fn parse_csv_answers(csv: &str) -> Option<[String; 2]> {
+    let mut records = output::parse_csv(csv)?;
+    if records.len() < 2 || records[1].len() < 3 {
+        return None;
+    }
+    let mut fields = records.swap_remove(1).into_iter().skip(1);
+    Some([fields.next()?, fields.next()?])
+}
+
+/// Describe how the answers changed since the previous run. `None` stands
+/// for a run that produced no answers.
+pub fn diff_answers(previous: Option<&[String; 2]>, current: Option<&[String; 2]>) -> String {
+    let mut out = String::new();
+    for part in 0..2 {
+        let before = previous.map(|a| a[part].as_str());
+        let after = current.map(|a| a[part].as_str());
+        let line = match (before, after) {
+            (_, None) => String::from("no answer"),
+            (None, Some(a)) => a.to_string(),
+            (Some(b), Some(a)) if b == a => format!("{} (unchanged)", a),
+            (Some(b), Some(a)) => format!("{} (was {})", a, b),
+        };
+        writeln!(out, "  Part {}: {}", part + 1, line).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(a: &str, b: &str) -> [String; 2] {
+        [a.to_string(), b.to_string()]
+    }
+
+    #[test]
+    fn csv_answers() {
+        let csv = "day,part1,part2,part1_status\n5,816,539,pass\n";
+        assert_eq!(parse_csv_answers(csv), Some(answers("816", "539")));
+        assert_eq!(parse_csv_answers("day,part1,part2\n"), None);
+        let csv = "day,part1,part2\n8,\"a,\"\"b\"\"\",\"two\nlines\"\n";
+        assert_eq!(parse_csv_answers(csv), Some(answers("a,\"b\"", "two\nlines")));
+    }
+
+    #[test]
+    fn diff() {
+        let old = answers("1", "2");
+        assert_eq!(diff_answers(None, Some(&old)), "  Part 1: 1\n  Part 2: 2\n");
+        assert_eq!(diff_answers(Some(&old), Some(&answers("1", "3"))),
+                   "  Part 1: 1 (unchanged)\n  Part 2: 3 (was 2)\n");
+        assert_eq!(diff_answers(Some(&old), None), "  Part 1: no answer\n  Part 2: no answer\n");
+    }
+
+    #[test]
+    fn poll_reports_changes() {
+        let path = env::temp_dir().join(format!("aoc2020-watch-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut watcher = Watcher::new(vec![path.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(&path, "a").unwrap();
+        assert_eq!(watcher.poll(), vec![path.as_path()]);
+        assert!(watcher.poll().is_empty());
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.poll(), vec![path.as_path()]);
+    }
+}
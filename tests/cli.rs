@@ -0,0 +1,250 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output, Stdio};
+use std::thread::{self, JoinHandle};
+
+fn run(args: &[&str], envs: &[(&str, &str)], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_adventofcode2020"))
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    if let Some(s) = stdin {
+        child.stdin.take().unwrap().write_all(s.as_bytes()).unwrap();
+    }
+    child.wait_with_output().unwrap()
+}
+
+/// A fresh directory unique to one test of this process, removed on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("aoc2020-cli-{}-{}", name, process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Answer a single request on localhost with `body`. Returns the base URL
+/// and a handle yielding the request line and body that were received.
+fn serve_once(body: &'static str) -> (String, JoinHandle<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                content_length = v.trim().parse().unwrap();
+            }
+            if line == "\r\n" { break; }
+        }
+        let mut body_in = vec![0; content_length];
+        reader.read_exact(&mut body_in).unwrap();
+        write!(reader.into_inner(), "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        (request_line, String::from_utf8(body_in).unwrap())
+    });
+    (base_url, server)
+}
+
+const DAY02_EXAMPLE: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+
+#[test]
+fn input_from_stdin() {
+    let output = run(&["02", "--input", "-", "--format", "csv"], &[], Some(DAY02_EXAMPLE));
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.lines().nth(1).unwrap().starts_with("2,2,1,unknown,unknown,"));
+}
+
+#[test]
+fn input_path_override() {
+    let dir = TempDir::new("input-path");
+    let path = dir.join("example.txt");
+    std::fs::write(&path, DAY02_EXAMPLE).unwrap();
+
+    let output = run(&["2", "--input", path.to_str().unwrap(), "--format", "csv"], &[], None);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("2,2,1,"));
+}
+
+#[test]
+fn input_dir_from_env() {
+    let dir = TempDir::new("input-dir");
+    std::fs::write(dir.join("day02.txt"), DAY02_EXAMPLE).unwrap();
+
+    let output = run(&["2", "--format", "csv"], &[("AOC_INPUT_DIR", dir.to_str().unwrap())], None);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("2,2,1,"));
+}
+
+#[test]
+fn input_override_needs_single_day() {
+    let output = run(&["all", "--input", "-"], &[], Some(""));
+    assert!(!output.status.success());
+}
+
+#[test]
+fn fetch_from_local_server() {
+    let (base_url, server) = serve_once("1-3 a: abcde\n");
+
+    let dir = TempDir::new("fetch");
+    let envs = [
+        ("AOC_BASE_URL", base_url.as_str()),
+        ("AOC_SESSION", "token"),
+        ("AOC_INPUT_DIR", dir.to_str().unwrap()),
+    ];
+    assert!(run(&["fetch", "08"], &envs, None).status.success());
+    assert_eq!(server.join().unwrap().0, "GET /2020/day/8/input HTTP/1.1\r\n");
+    assert_eq!(std::fs::read_to_string(dir.join("day08.txt")).unwrap(), "1-3 a: abcde\n");
+
+    // The server is gone, so a second fetch must be served from the cache.
+    assert!(run(&["fetch", "08"], &envs, None).status.success());
+}
+
+#[test]
+fn submit_to_local_server() {
+    let (base_url, server) = serve_once("<p>That's not the right answer; your answer is too low.</p>");
+
+    let dir = TempDir::new("submit");
+    std::fs::write(dir.join("day02.txt"), DAY02_EXAMPLE).unwrap();
+    let log = dir.join("submissions.log");
+    let envs = [
+        ("AOC_BASE_URL", base_url.as_str()),
+        ("AOC_SESSION", "token"),
+        ("AOC_INPUT_DIR", dir.to_str().unwrap()),
+        ("AOC_SUBMISSION_LOG", log.to_str().unwrap()),
+    ];
+    let output = run(&["submit", "2", "1"], &envs, None);
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "too-low\n");
+    assert_eq!(server.join().unwrap(),
+               (String::from("POST /2020/day/2/answer HTTP/1.1\r\n"), String::from("level=1&answer=2")));
+
+    // The server is gone; the logged wrong answer must not be sent again.
+    let output = run(&["submit", "2", "1"], &envs, None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("already submitted"));
+}
+
+#[test]
+fn submit_solves_only_the_submitted_part() {
+    let dir = TempDir::new("submit-part");
+    // Part 2 has no answer for a single seat, which must not stop part 1.
+    std::fs::write(dir.join("day05.txt"), "FBFBBFFRLR\n").unwrap();
+    let log = dir.join("submissions.log");
+    let envs = [
+        ("AOC_BASE_URL", "http://127.0.0.1:1"),
+        ("AOC_SESSION", "token"),
+        ("AOC_INPUT_DIR", dir.to_str().unwrap()),
+        ("AOC_SUBMISSION_LOG", log.to_str().unwrap()),
+    ];
+    let output = run(&["submit", "5", "1"], &envs, None);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Day 05 / Part 1: submitting 357"), "{}", stderr);
+}
+
+#[test]
+fn bench_saves_and_compares_baseline() {
+    let dir = TempDir::new("bench");
+    std::fs::write(dir.join("day02.txt"), DAY02_EXAMPLE).unwrap();
+    let baseline = dir.join("baseline.txt");
+    let save = format!("--save-baseline={}", baseline.display());
+    let envs = [("AOC_INPUT_DIR", dir.to_str().unwrap())];
+
+    let output = run(&["bench", "2", "--iterations", "5", &save], &envs, None);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Day 02: 5 iterations, "));
+    assert!(stdout.contains("  total "));
+    assert!(std::fs::read_to_string(&baseline).unwrap().lines().nth(1).unwrap().starts_with("2 "));
+
+    // A huge threshold makes the comparison independent of timing noise.
+    let compare = format!("--compare={}", baseline.display());
+    let output = run(&["bench", "2", "--iterations", "5", &compare, "--threshold", "1e9"], &envs, None);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("02     total "));
+}
+
+#[test]
+fn generate_writes_input_and_answers() {
+    let dir = TempDir::new("generate");
+    let path = dir.join("day01.txt");
+
+    let output = run(&["generate", "1", path.to_str().unwrap(), "--count", "300", "--seed", "42"], &[], None);
+    assert!(output.status.success());
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 300);
+
+    let answers = std::fs::read_to_string(dir.join("day01.toml")).unwrap();
+    let output = run(&["1", "--input", path.to_str().unwrap(), "--format", "csv"], &[], None);
+    let row = String::from_utf8(output.stdout).unwrap().lines().nth(1).unwrap().to_string();
+    let fields: Vec<&str> = row.split(',').collect();
+    assert!(answers.ends_with(&format!("part1 = {}\npart2 = {}\n", fields[1], fields[2])));
+}
+
+#[test]
+fn audit_with_composed_policy() {
+    let output = run(&["audit", "sled and not toboggan", "--input", "-"], &[], Some(DAY02_EXAMPLE));
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("1 of 3 passwords satisfy (sled and not toboggan)\n"));
+    assert!(stdout.contains("sled: too-few"));
+
+    let output = run(&["audit", "sled", "--input", "-", "--format", "csv"], &[], Some(DAY02_EXAMPLE));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(),
+               "line,password,policy,reason,message\n2,cdefg,sled,too-few,\"'b' occurs 0 times, allowed 1-3\"\n");
+
+    let accents = "1-2 x: e\u{301}x\n";
+    let output = run(&["audit", "toboggan", "--input", "-", "--unit", "grapheme"], &[], Some(accents));
+    assert!(String::from_utf8(output.stdout).unwrap().starts_with("1 of 1 passwords"));
+    let output = run(&["audit", "toboggan", "--input", "-", "--unit", "byte", "--format", "csv"], &[], Some(accents));
+    assert!(String::from_utf8(output.stdout).unwrap().contains("neither-position"));
+
+    let output = run(&["audit", "sled and bobsled", "--input", "-"], &[], Some(DAY02_EXAMPLE));
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("unknown policy 'bobsled'"));
+}
+
+#[test]
+fn slopes_counts_and_searches() {
+    let map = std::fs::read_to_string("examples/day03/example.txt").unwrap();
+    let output = run(&["slopes", "3/1", "-8/1", "--input", "-"], &[], Some(&map));
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(),
+               "   Slope    Trees\n     3/1        7\n    -8/1        7\nProduct: 49\n");
+
+    let output = run(&["slopes", "--search", "3", "--input", "-"], &[], Some(&map));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(),
+               "Fewest trees: 0 along 1/3\nMost trees: 7 along 3/1\n");
+
+    // Rejected before the input is read.
+    let output = run(&["slopes", "1/0", "--input", "-"], &[], None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("needs to move down"));
+}
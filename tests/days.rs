@@ -0,0 +1,50 @@
+use adventofcode2020::{common, find_day, Answer, DAYS};
+
+fn run_day(number: u32) -> (Answer, Answer) {
+    let day = find_day(number).unwrap();
+    let input = common::file_as_string(&day.input_path()).unwrap();
+    let answers = (day.solve)(&input).unwrap().answers;
+    (answers.part1, answers.part2)
+}
+
+#[test]
+fn registry_is_ordered() {
+    for (i, day) in DAYS.iter().enumerate() {
+        assert_eq!(day.number, i as u32 + 1);
+    }
+}
+
+#[test]
+fn day01() {
+    assert_eq!(run_day(1), (Answer::Number(870331), Answer::Number(283025088)));
+}
+
+#[test]
+fn day02() {
+    assert_eq!(run_day(2), (Answer::Number(607), Answer::Number(321)));
+}
+
+#[test]
+fn day03() {
+    assert_eq!(run_day(3), (Answer::Number(289), Answer::Number(5522401584)));
+}
+
+#[test]
+fn day04() {
+    assert_eq!(run_day(4), (Answer::Number(214), Answer::Number(160)));
+}
+
+#[test]
+fn day05() {
+    assert_eq!(run_day(5), (Answer::Number(816), Answer::Number(539)));
+}
+
+#[test]
+fn day06() {
+    assert_eq!(run_day(6), (Answer::Number(6443), Answer::Number(3232)));
+}
+
+#[test]
+fn day07() {
+    assert_eq!(run_day(7), (Answer::Number(128), Answer::Number(20189)));
+}
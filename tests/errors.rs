@@ -0,0 +1,60 @@
+use adventofcode2020::{find_day, Error};
+
+fn solve(day: u32, input: &str) -> Result<(), Error> {
+    (find_day(day).unwrap().solve)(input).map(|_| ())
+}
+
+fn parse_error_at(result: Result<(), Error>) -> (usize, usize) {
+    match result {
+        Err(Error::Parse(d)) => (d.line, d.column()),
+        r => panic!("expected a parse error, got {:?}", r),
+    }
+}
+
+#[test]
+fn day01_bad_number() {
+    assert_eq!(parse_error_at(solve(1, "1721\n97x9\n")), (2, 1));
+}
+
+#[test]
+fn day01_no_answer() {
+    assert!(matches!(solve(1, ""), Err(Error::MissingAnswer(_))));
+    assert!(matches!(solve(1, "1\n2\n3"), Err(Error::MissingAnswer(_))));
+}
+
+#[test]
+fn day02_malformed_record() {
+    assert_eq!(parse_error_at(solve(2, "1-3 a: abcde\n1-3 b cdefg")).0, 2);
+    assert_eq!(parse_error_at(solve(2, "1 a: abcde")).0, 1);
+}
+
+#[test]
+fn day03_bad_tile() {
+    assert_eq!(parse_error_at(solve(3, "..#\n.x.")), (2, 2));
+    assert_eq!(parse_error_at(solve(3, "..#\n..")).0, 2);
+    assert!(matches!(solve(3, ""), Err(Error::Validation(_))));
+}
+
+#[test]
+fn day04_bad_field() {
+    assert_eq!(parse_error_at(solve(4, "byr:1937 foo:bar")), (1, 10));
+    assert_eq!(parse_error_at(solve(4, "byr:1937\nhgt:xcm")).0, 2);
+    assert_eq!(parse_error_at(solve(4, "iyr:20x7")), (1, 5));
+}
+
+#[test]
+fn day05_bad_pass() {
+    assert_eq!(parse_error_at(solve(5, "FBFBBFFRLR\nFBFBBFXRLR")), (2, 7));
+    assert_eq!(parse_error_at(solve(5, "FBFBBFFRLRR")), (1, 11));
+}
+
+#[test]
+fn day06_bad_answer() {
+    assert_eq!(parse_error_at(solve(6, "abc\n\na\nbC")), (4, 2));
+}
+
+#[test]
+fn day07_bad_rule() {
+    assert_eq!(parse_error_at(solve(7, "shiny gold bags contain x dark red bags.")).0, 1);
+    assert_eq!(parse_error_at(solve(7, "shiny gold bags")).0, 1);
+}
@@ -0,0 +1,38 @@
+//! Solves every example in `examples/dayNN/` with its registered day.
+//!
+//! There is one test per day in [`DAYS`], named `dayNN`, so days added with
+//! `new` are picked up without touching this file. The tests run under
+//! `libtest-mimic`, which takes the standard harness flags, e.g.
+//! `cargo test --test examples -- --exact day04` or `-- --list`.
+
+use std::path::Path;
+
+use adventofcode2020::{examples, Day, DAYS};
+use libtest_mimic::{Arguments, Failed, Trial};
+
+/// Checks every example of `day`, failing with one line per wrong answer.
+fn check_examples(day: &Day) -> Result<(), Failed> {
+    let loaded = examples::load(Path::new(examples::DEFAULT_DIR), day.number)
+        .map_err(|e| e.to_string())?;
+    if loaded.is_empty() {
+        return Err(format!("no examples in {}",
+                           examples::day_dir(Path::new(examples::DEFAULT_DIR), day.number).display()).into());
+    }
+    let failures: Vec<String> = examples::check(day, &loaded).iter()
+        .filter(|c| !c.passed())
+        .map(|c| format!("{} part {}: expected {}, got {:?}", c.example, c.part, c.expected, c.actual))
+        .collect();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n").into())
+    }
+}
+
+fn main() {
+    let args = Arguments::from_args();
+    let trials = DAYS.iter()
+        .map(|day| Trial::test(format!("day{:02}", day.number), move || check_examples(day)))
+        .collect();
+    libtest_mimic::run(&args, trials).exit();
+}
@@ -0,0 +1,275 @@
+//! Property tests of the parsers: any input is either parsed or rejected
+//! with an error, never a panic, and formatted values parse back unchanged.
+//!
+//! Failing inputs are shrunk by proptest before they are reported.
+
+use std::convert::TryFrom;
+use std::fs;
+use std::time::Duration;
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::{select, Index};
+use proptest::test_runner::{Config, TestError, TestRunner};
+
+use adventofcode2020::answers::ExpectedAnswers;
+use adventofcode2020::bench::{Baseline, StageBaseline};
+use adventofcode2020::day02;
+use adventofcode2020::day05::BoardingPass;
+use adventofcode2020::fuzz::TARGETS;
+use adventofcode2020::submit::{self, Attempt, Outcome, SubmissionLog, Verdict};
+use adventofcode2020::Answer;
+
+/// Bytes that are meaningful to some parser, and a few that are not UTF-8.
+const INTERESTING: &[u8] = b"0123456789-+:=#[]\"\\ \t\r\n()abcdeFBLRcminxyz.\x00\x80\xc3\xa9\xe2\x80\x8d\xff";
+
+/// Well formed inputs to mutate, per target.
+fn seeds(name: &str) -> Vec<Vec<u8>> {
+    let mut seeds: Vec<Vec<u8>> = match name {
+        "day02" => vec!["1-2 e: e\u{301}e\n3-1 \u{1F1F8}: \u{1F1F8}\u{1F1EA}x\n".as_bytes().to_vec()],
+        "day02_policy" => vec![b"sled and not (toboggan or part1)\n1-3 a: abcde\n1-3 b: cdefg".to_vec()],
+        "day05_pass" => vec![b"FBFBBFFRLR".to_vec()],
+        "day07" => vec![b"shiny gold bags contain 99999999999 dark red bags.\n\
+                          dark red bags contain 99999999999 pale blue bags.\n".to_vec()],
+        "answers" => vec![b"[day01]\npart1 = 514579\npart2 = \"a \\\"b\\\"\"\n\n[day02]\npart1 = 2\n".to_vec()],
+        "example_sidecar" => vec![b"# comment\npart1 = 7\npart2 = \"x\"\n".to_vec()],
+        "bench_baseline" => vec![b"# day parse_ns parse_sd_ns part1_ns part1_sd_ns part2_ns part2_sd_ns total_ns total_sd_ns\n4 10 1 20 2 30 3 60 4\n".to_vec()],
+        "submission_log" => vec![
+            b"1600000000\t1\t1\ttoo-high\t-\t42\n1600000060\t1\t2\twait\t55\tabc\n".to_vec(),
+            b"You gave an answer too recently; you have 1h 1m 5s left to wait.".to_vec(),
+            b"That's not the right answer. Please wait 5 minutes before trying again.".to_vec(),
+        ],
+        _ => Vec::new(),
+    };
+    if let Some(day) = name.strip_prefix("day").and_then(|d| d.parse::<u32>().ok()) {
+        let dir = format!("examples/day{:02}", day);
+        for entry in fs::read_dir(dir).into_iter().flatten() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|e| e == "txt") {
+                seeds.push(fs::read(path).unwrap());
+            }
+        }
+    }
+    seeds.push(Vec::new());
+    seeds
+}
+
+/// A run of digits long enough to overflow the counts and times it lands
+/// in, whether or not it still fits in a `u64` itself.
+const DIGITS: &str = "[0-9]{12,22}";
+
+#[derive(Debug, Clone)]
+enum Edit {
+    Replace(Index, u8),
+    Insert(Index, Vec<u8>),
+    /// Append digits to the first number at or after the index.
+    Lengthen(Index, String),
+    Delete(Index, Index),
+    Duplicate(Index, Index),
+    Truncate(Index),
+}
+
+fn edit() -> impl Strategy<Value = Edit> {
+    prop_oneof![
+        (any::<Index>(), select(INTERESTING)).prop_map(|(at, b)| Edit::Replace(at, b)),
+        (any::<Index>(), vec(select(INTERESTING), 1..8)).prop_map(|(at, f)| Edit::Insert(at, f)),
+        (any::<Index>(), DIGITS).prop_map(|(at, digits)| Edit::Lengthen(at, digits)),
+        (any::<Index>(), any::<Index>()).prop_map(|(a, b)| Edit::Delete(a, b)),
+        (any::<Index>(), any::<Index>()).prop_map(|(a, b)| Edit::Duplicate(a, b)),
+        any::<Index>().prop_map(Edit::Truncate),
+    ]
+}
+
+fn apply(mut input: Vec<u8>, edits: &[Edit]) -> Vec<u8> {
+    for edit in edits {
+        let len = input.len();
+        let range = |a: &Index, b: &Index| {
+            let (a, b) = (a.index(len + 1), b.index(len + 1));
+            a.min(b)..a.max(b)
+        };
+        match edit {
+            Edit::Replace(at, b) if len > 0 => input[at.index(len)] = *b,
+            Edit::Replace(..) => {},
+            Edit::Insert(at, fragment) => {
+                let at = at.index(len + 1);
+                input.splice(at..at, fragment.iter().copied());
+            },
+            Edit::Lengthen(at, digits) => {
+                let at = at.index(len + 1);
+                let end = input[at..].iter().position(u8::is_ascii_digit)
+                    .map(|i| at + i + input[at + i..].iter().take_while(|b| b.is_ascii_digit()).count())
+                    .unwrap_or(at);
+                input.splice(end..end, digits.bytes());
+            },
+            Edit::Delete(a, b) => {
+                input.drain(range(a, b));
+            },
+            Edit::Duplicate(a, b) => {
+                let range = range(a, b);
+                let chunk = input[range.clone()].to_vec();
+                input.splice(range.start..range.start, chunk);
+            },
+            Edit::Truncate(at) => input.truncate(at.index(len + 1)),
+        }
+    }
+    input
+}
+
+/// Mostly seeds with one to four edits, sometimes random bytes.
+fn input(name: &str) -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        1 => vec(any::<u8>(), 0..64),
+        9 => (select(seeds(name)), vec(edit(), 1..5)).prop_map(|(seed, edits)| apply(seed, &edits)),
+    ]
+}
+
+/// The `name` and `path` of every `[[bin]]` table in a Cargo manifest.
+fn manifest_bins(manifest: &str) -> Vec<(String, String)> {
+    let mut bins = Vec::new();
+    let mut current: Option<(String, String)> = None;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            bins.extend(current.take());
+            if line == "[[bin]]" {
+                current = Some(Default::default());
+            }
+        } else if let (Some((name, path)), Some((key, value))) = (current.as_mut(), line.split_once('=')) {
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "name" => *name = value,
+                "path" => *path = value,
+                _ => {},
+            }
+        }
+    }
+    bins.extend(current);
+    bins
+}
+
+#[test]
+fn every_target_has_a_fuzz_binary() {
+    let mut bins = manifest_bins(&fs::read_to_string("fuzz/Cargo.toml").unwrap());
+    bins.sort();
+    let mut names: Vec<&str> = TARGETS.iter().map(|t| t.name).collect();
+    names.sort();
+    assert_eq!(bins.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), names);
+    for (name, path) in &bins {
+        assert_eq!(path, &format!("fuzz_targets/{}.rs", name));
+        let source = fs::read_to_string(format!("fuzz/{}", path)).unwrap();
+        assert!(source.contains(&format!("target(\"{}\")", name)), "{} runs the wrong target", path);
+    }
+    assert_eq!(manifest_bins("[package]\nname = \"x\"\n\n[[bin]]\nname = \"a\"\n\n[[bin]]\npath = \"b.rs\"\nname = \"b\"\n"),
+               vec![(String::from("a"), String::new()), (String::from("b"), String::from("b.rs"))]);
+}
+
+#[test]
+fn parsers_never_panic() {
+    for target in TARGETS {
+        let mut runner = TestRunner::new(Config { cases: 1000, ..Config::default() });
+        let result = runner.run(&input(target.name), |data| {
+            let _ = target.run(&data);
+            Ok(())
+        });
+        match result {
+            Ok(()) => {},
+            Err(TestError::Fail(reason, data)) =>
+                panic!("{} failed on {:?}: {}", target.name, String::from_utf8_lossy(&data), reason),
+            Err(e) => panic!("{}: {}", target.name, e),
+        }
+    }
+}
+
+/// Printable text of up to 12 characters.
+const TEXT: &str = "\\PC{0,12}";
+
+fn answer() -> impl Strategy<Value = Answer> {
+    prop_oneof![any::<u64>().prop_map(Answer::Number), TEXT.prop_map(Answer::Text)]
+}
+
+fn attempt() -> impl Strategy<Value = Attempt> {
+    let outcomes = vec![Outcome::Correct, Outcome::TooHigh, Outcome::TooLow, Outcome::Incorrect,
+                        Outcome::Wait, Outcome::AlreadyCompleted, Outcome::Unknown];
+    (any::<u64>(), 1..26u32, 1..3u8, TEXT, select(outcomes), option::of(any::<u64>()))
+        .prop_map(|(time, day, part, answer, outcome, wait_secs)| Attempt {
+            time,
+            day,
+            part,
+            answer: answer.trim().to_string(),
+            verdict: Verdict { outcome, wait_secs },
+        })
+}
+
+proptest! {
+    #[test]
+    fn password_records_round_trip(start in 0..100usize, end in 0..100usize,
+                                   c in "[^\\s:]", password in "[^\\s:]{0,20}") {
+        let line = format!("{}-{} {}: {}", start, end, c, password);
+        let record = day02::parse_record(1, &line).unwrap();
+        prop_assert_eq!((record.range_start, record.range_end, record.charachter.to_string(), record.password),
+                        (start, end, c, password));
+    }
+
+    #[test]
+    fn expected_answers_round_trip(answers in vec((1..26u32, 1..3u8, answer()), 0..6)) {
+        let mut expected = ExpectedAnswers::default();
+        for (day, part, answer) in answers {
+            expected.insert(day, part, answer);
+        }
+        prop_assert_eq!(ExpectedAnswers::parse(&expected.to_toml()).unwrap(), expected);
+    }
+
+    #[test]
+    fn baselines_round_trip(days in vec((1..26u32, prop::array::uniform4((any::<u64>(), any::<u64>()))), 0..5)) {
+        let mut baseline = Baseline::default();
+        for (day, stages) in days {
+            let stages = stages.map(|(mean, stddev)| StageBaseline {
+                mean: Duration::from_nanos(mean),
+                stddev: Duration::from_nanos(stddev),
+            });
+            baseline.stages.insert(day, stages);
+        }
+        prop_assert_eq!(Baseline::parse(&baseline.to_text()).unwrap(), baseline);
+    }
+
+    #[test]
+    fn submission_logs_round_trip(attempts in vec(attempt(), 0..5)) {
+        let log = SubmissionLog { attempts };
+        prop_assert_eq!(SubmissionLog::parse(&log.to_text()), log);
+    }
+
+    #[test]
+    fn wait_times_are_added_up_or_rejected(parts in vec(("[0-9]{1,25}", select(vec![3600u128, 60, 1])), 1..4)) {
+        let unit = |secs| match secs { 3600 => 'h', 60 => 'm', _ => 's' };
+        let phrase: Vec<String> = parts.iter().map(|(n, secs)| format!("{}{}", n, unit(*secs))).collect();
+        let body = format!("You gave an answer too recently; you have {} left to wait.", phrase.join(" "));
+        let total: u128 = parts.iter().map(|(n, secs)| n.parse::<u128>().unwrap() * secs).sum();
+        prop_assert_eq!(submit::parse_response(&body).wait_secs, u64::try_from(total).ok());
+    }
+
+    #[test]
+    fn wait_minutes_are_rejected_when_too_long(n in "[0-9]{1,25}") {
+        let body = format!("That's not the right answer. Please wait {} minutes before trying again.", n);
+        let secs = n.parse::<u128>().unwrap() * 60;
+        prop_assert_eq!(submit::parse_response(&body).wait_secs, u64::try_from(secs).ok());
+    }
+}
+
+#[test]
+fn boarding_passes_decode_their_seat() {
+    for seat_id in 0..1024 {
+        let code: String = (0..10).rev()
+            .map(|bit| {
+                let set = seat_id >> bit & 1 == 1;
+                match (bit >= 3, set) {
+                    (true, true) => 'B',
+                    (true, false) => 'F',
+                    (false, true) => 'R',
+                    (false, false) => 'L',
+                }
+            })
+            .collect();
+        let decoded = BoardingPass::new(&code).decode().unwrap();
+        assert_eq!((decoded.seat_id, decoded.row, decoded.column), (seat_id, seat_id / 8, seat_id % 8));
+    }
+}